@@ -1,4 +1,4 @@
-from collections.abc import Callable
+from collections.abc import Buffer, Callable, Sequence
 from typing import Final
 
 class Color:
@@ -63,26 +63,259 @@ class Music:
     volume: float
     speed: float
     loop: bool
+    loop_start: float
+    loop_end: float | None
     paused: bool
+    duration: float
+    position: float
+    is_playing: bool
+
+    def set_loop_region(self, start: float, end: float | None = None) -> None: ...
+    def stop(self, fade: float = 0.0) -> None: ...
+    def seek(self, seconds: float) -> None: ...
+    def fade_in(self, seconds: float, easing: str = "linear") -> None: ...
+    def fade_out(self, seconds: float, easing: str = "linear") -> None: ...
 
 class Sound:
     volume: float
     speed: float
+    max_voices: int | None
+    voice_count: int
 
 class Texture:
-    pass
+    width: int
+    height: int
+
+    def set_pixels(self, rect: tuple[int, int, int, int], rgba: bytes) -> None: ...
+    def set_pixel(self, x: int, y: int, color: Color) -> None: ...
+    def fill(self, color: Color) -> None: ...
+    def read_pixels(self) -> bytes: ...
+    def to_image(self) -> Image: ...
+
+class Image:
+    width: int
+    height: int
+
+    def __init__(self, width: int, height: int, color: Color | None = None): ...
+    @staticmethod
+    def load(path: str) -> Image: ...
+    def save(self, path: str) -> None: ...
+    def get_pixel(self, x: int, y: int) -> Color: ...
+    def set_pixel(self, x: int, y: int, color: Color) -> None: ...
+    def crop(self, x: int, y: int, width: int, height: int) -> Image: ...
+    def resize(self, width: int, height: int, filter: str | None = None) -> Image: ...
+    def flip_horizontal(self) -> Image: ...
+    def flip_vertical(self) -> Image: ...
+    def rotate90(self, turns: int = 1) -> Image: ...
+    def tint(self, color: Color) -> Image: ...
+    def blit(self, image: Image, x: int, y: int, blend: bool = True) -> None: ...
+    def to_texture(self, mipmaps: bool = False) -> Texture: ...
+
+class Atlas:
+    def names(self) -> list[str]: ...
+    def __getitem__(self, name: str) -> Texture: ...
+    def __contains__(self, name: str) -> bool: ...
+    def __len__(self) -> int: ...
+
+class LoadGroup:
+    progress: float
+    done: bool
+    errors: dict[str, str]
+
+    def get(self, path: str) -> Texture | Sound | None: ...
+    def __getitem__(self, path: str) -> Texture | Sound: ...
 
 class Assets:
+    hot_reload: bool
+    reload_errors: dict[str, str]
+
     def set_root(self, path: str) -> str: ...
-    def resolve_path(self, path: str) -> str: ...
+    def resolve_path(self, path: str) -> str | None: ...
+    def mount(
+        self, path: str, point: str | None = None, priority: int | None = None
+    ) -> None: ...
+    def mount_memory(
+        self,
+        name: str,
+        files: dict[str, bytes],
+        point: str | None = None,
+        priority: int | None = None,
+    ) -> None: ...
+    def unmount(self, name: str) -> bool: ...
+    def exists(self, path: str) -> bool: ...
+    def read_bytes(self, path: str) -> bytes: ...
+    def read_text(self, path: str) -> str: ...
     def music(self, path: str) -> Music: ...
     def sound(self, path: str) -> Sound: ...
-    def texture(self, path: str) -> Texture: ...
+    def texture(self, path: str, mipmaps: bool = False) -> Texture: ...
+    def animation(self, path: str) -> list[tuple[Texture, float]]: ...
+    def texture_from_bytes(
+        self, data: bytes, format: str | None = None, mipmaps: bool = False
+    ) -> Texture: ...
+    def texture_from_pixels(
+        self, width: int, height: int, rgba: bytes, mipmaps: bool = False
+    ) -> Texture: ...
+    def texture_from_image(self, image: Image, mipmaps: bool = False) -> Texture: ...
+    def image(self, path: str) -> Image: ...
+    def blank_texture(self, width: int, height: int, mipmaps: bool = False) -> Texture: ...
+    def sound_from_bytes(self, data: bytes) -> Sound: ...
+    def sound_from_samples(
+        self, samples: Sequence[float] | Buffer, sample_rate: int, channels: int = 1
+    ) -> Sound: ...
+    def atlas(self, paths: list[str], padding: int | None = None) -> Atlas: ...
+    def atlas_dir(self, path: str, padding: int | None = None) -> Atlas: ...
+    def load_async(self, paths: list[str]) -> LoadGroup: ...
+    def unload(self, path: str) -> bool: ...
+    def clear(self) -> None: ...
+
+class Effect:
+    kind: str
+
+    def set(self, param: str, value: float, duration: float = 0.0) -> None: ...
+
+class Track:
+    name: str
+    volume: float
+    muted: bool
+    solo: bool
+
+    def fade(self, volume: float, duration: float) -> None: ...
+    def add_effect(self, kind: str, **params: float) -> Effect: ...
+
+class Emitter:
+    position: Vec2
+
+class Analyser:
+    size: int
+    sample_rate: int
+    level: float
+    peak: float
+
+    def spectrum(self, bands: int | None = None) -> list[float]: ...
+
+class ClockTime:
+    beat: int
+
+class Clock:
+    bpm: float
+    current_beat: int
+    fraction: float
+    position: float
+    ticking: bool
+
+    def change_bpm(self, bpm: float, duration: float = 0.0) -> None: ...
+    def beat(self, beat: int) -> ClockTime: ...
+    def next_beat(self, every: int = 1) -> ClockTime: ...
+    def start(self) -> None: ...
+    def pause(self) -> None: ...
+    def stop(self) -> None: ...
+
+class Signal:
+    def __add__(self, other: Signal) -> Signal: ...
+    def __mul__(self, other: Signal | float) -> Signal: ...
+    def __rmul__(self, other: Signal | float) -> Signal: ...
+    def render(
+        self,
+        duration: float,
+        envelope: tuple[float, float, float, float] | None = None,
+        sample_rate: int = 44100,
+    ) -> Sound: ...
+
+class Synth:
+    def tone(
+        self,
+        frequency: float,
+        duration: float,
+        wave: str = "square",
+        envelope: tuple[float, float, float, float] | None = None,
+        volume: float = 1.0,
+        end_frequency: float | None = None,
+        sample_rate: int = 44100,
+    ) -> Sound: ...
+    def oscillator(
+        self, wave: str = "sine", frequency: float = 440.0, volume: float = 1.0
+    ) -> Signal: ...
+    def noise(self, volume: float = 1.0, frequency: float = 22050.0) -> Signal: ...
+
+class Generator:
+    volume: float
+    emitter: Emitter | None
+    is_playing: bool
+
+    def stop(self, fade: float = 0.0) -> None: ...
+
+class SoundPool:
+    sounds: list[Sound]
+    volume: tuple[float, float]
+    speed: tuple[float, float]
+    cooldown: float
+
+    def __init__(
+        self,
+        sounds: list[Sound],
+        volume: tuple[float, float] = (1.0, 1.0),
+        speed: tuple[float, float] = (1.0, 1.0),
+        cooldown: float = 0.0,
+    ): ...
+
+class Voice:
+    volume: float
+    speed: float
+    panning: float
+    emitter: Emitter | None
+    is_playing: bool
+
+    def stop(self, fade: float = 0.0) -> None: ...
+    def pause(self, fade: float = 0.0) -> None: ...
+    def resume(self, fade: float = 0.0) -> None: ...
 
 class Audio:
     volume: float
+    listener: Vec2 | Camera
+
+    def track(self, name: str, parent: str | None = None) -> Track: ...
+    def tracks(self) -> list[str]: ...
+    def clock(self, bpm: float, start: bool = True) -> Clock: ...
+    def analyser(self, track: str | None = None, size: int = 1024) -> Analyser: ...
+    def play(
+        self,
+        sound: Music | Sound | SoundPool | Signal,
+        track: str | None = None,
+        position: Vec2 | None = None,
+        min_distance: float = 0.0,
+        max_distance: float = 1000.0,
+        rolloff: float = 1.0,
+        start_at: ClockTime | None = None,
+    ) -> Voice | Generator | Emitter | None: ...
+    def crossfade(
+        self,
+        from_music: Music,
+        to_music: Music,
+        seconds: float,
+        track: str | None = None,
+    ) -> None: ...
 
-    def play(self, sound: Music | Sound) -> None: ...
+class SpriteBatch:
+    def __init__(self): ...
+    def add(
+        self,
+        texture: Texture,
+        position: Vec2,
+        rotation: float | None,
+        scale: Vec2 | None,
+        tint: Color | None,
+    ) -> int: ...
+    def set(
+        self,
+        index: int,
+        texture: Texture,
+        position: Vec2,
+        rotation: float | None,
+        scale: Vec2 | None,
+        tint: Color | None,
+    ) -> None: ...
+    def clear(self) -> None: ...
+    def __len__(self) -> int: ...
 
 class Draw:
     def set_camera(self, camera: Camera | None) -> None: ...
@@ -94,6 +327,7 @@ class Draw:
         scale: Vec2 | None,
         tint: Color | None,
     ) -> None: ...
+    def batch(self, batch: SpriteBatch) -> None: ...
 
 class Input:
     def key_down(self, code: str) -> bool: ...
@@ -107,6 +341,7 @@ class Input:
 
 class Stats:
     def fps(self) -> float: ...
+    def culled(self) -> int: ...
 
 class Time:
     def delta(self) -> float: ...
@@ -124,6 +359,7 @@ audio: Audio
 draw: Draw
 input: Input
 stats: Stats
+synth: Synth
 time: Time
 window: Window
 
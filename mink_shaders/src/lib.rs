@@ -1 +1,3 @@
 pub const SPRITE: &str = include_str!("sprite.wgsl");
+pub const SPRITE_ARRAY: &str = include_str!("sprite_array.wgsl");
+pub const MIPMAP: &str = include_str!("mipmap.wgsl");
@@ -4,11 +4,10 @@ struct VertexInput {
 }
 
 struct InstanceInput {
-    @location(2) matrix_1: vec4<f32>,
-    @location(3) matrix_2: vec4<f32>,
-    @location(4) matrix_3: vec4<f32>,
-    @location(5) matrix_4: vec4<f32>,
-    @location(6) color: vec4<f32>,
+    @location(2) transform: vec4<f32>,
+    @location(3) translation: vec2<f32>,
+    @location(4) color: vec4<f32>,
+    @location(5) uv: vec4<f32>,
 }
 
 struct VertexOutput {
@@ -17,23 +16,27 @@ struct VertexOutput {
     @location(1) color: vec4<f32>,
 }
 
+struct CameraUniform {
+    view_projection: mat4x4<f32>,
+}
+
 @group(0) @binding(0)
 var texture: texture_2d<f32>;
 @group(0) @binding(1)
 var texture_sampler: sampler;
 
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
 @vertex
 fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
-    let matrix = mat4x4<f32>(
-        instance.matrix_1,
-        instance.matrix_2,
-        instance.matrix_3,
-        instance.matrix_4,
-    );
+    let world = instance.transform.xy * vertex.position.x
+        + instance.transform.zw * vertex.position.y
+        + instance.translation;
 
     var out: VertexOutput;
-    out.clip_position = matrix * vec4<f32>(vertex.position, 0.0, 1.0) ;
-    out.uv = vertex.uv;
+    out.clip_position = camera.view_projection * vec4<f32>(world, 0.0, 1.0);
+    out.uv = instance.uv.xy + vertex.uv * instance.uv.zw;
     out.color = instance.color;
 
     return out;
@@ -42,4 +45,4 @@ fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return textureSample(texture, texture_sampler, in.uv) * in.color;
-}
\ No newline at end of file
+}
@@ -0,0 +1,52 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+struct InstanceInput {
+    @location(2) transform: vec4<f32>,
+    @location(3) translation: vec2<f32>,
+    @location(4) color: vec4<f32>,
+    @location(5) uv: vec4<f32>,
+    @location(6) texture: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) texture: u32,
+}
+
+struct CameraUniform {
+    view_projection: mat4x4<f32>,
+}
+
+// Must match `TextureArrays::SIZE`.
+@group(0) @binding(0)
+var textures: binding_array<texture_2d<f32>, 16>;
+@group(0) @binding(1)
+var texture_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let world = instance.transform.xy * vertex.position.x
+        + instance.transform.zw * vertex.position.y
+        + instance.translation;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_projection * vec4<f32>(world, 0.0, 1.0);
+    out.uv = instance.uv.xy + vertex.uv * instance.uv.zw;
+    out.color = instance.color;
+    out.texture = instance.texture;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(textures[in.texture], texture_sampler, in.uv) * in.color;
+}
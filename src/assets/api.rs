@@ -1,9 +1,15 @@
 use pyo3::prelude::*;
 
-use crate::assets::{music::Music, sound::Sound, texture::Texture, Assets};
+use crate::assets::{
+    atlas::Atlas, bitmap::Bitmap, loader::LoadGroup, music::Music, sound::Sound, texture::Texture,
+    Assets,
+};
 
 pub fn install(parent: &Bound<'_, PyModule>) -> PyResult<()> {
     parent.add_class::<Assets>()?;
+    parent.add_class::<Atlas>()?;
+    parent.add_class::<Bitmap>()?;
+    parent.add_class::<LoadGroup>()?;
     parent.add_class::<Music>()?;
     parent.add_class::<Sound>()?;
     parent.add_class::<Texture>()?;
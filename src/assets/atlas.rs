@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use pyo3::{exceptions::PyKeyError, prelude::*};
+
+use crate::assets::texture::Texture;
+
+/// Named regions of one or more packed atlas textures.
+#[pyclass]
+pub struct Atlas {
+    pub regions: HashMap<String, Texture>,
+}
+
+#[pymethods]
+impl Atlas {
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.regions.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn __getitem__(&self, name: &str) -> PyResult<Texture> {
+        self.regions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PyKeyError::new_err(format!("No atlas region named '{name}'")))
+    }
+
+    pub fn __contains__(&self, name: &str) -> bool {
+        self.regions.contains_key(name)
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.regions.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    width: u32,
+}
+
+struct Page {
+    shelves: Vec<Shelf>,
+    width: u32,
+    height: u32,
+}
+
+/// Packs rectangles into pages of at most `max_size` using shelves, tallest first.
+/// Each rectangle is surrounded by `padding` pixels, and placements point at the padded cell.
+/// Rectangles too large for a page get a page of their own. Returns placements in input order,
+/// along with the size of each page.
+pub fn pack(
+    sizes: &[(u32, u32)],
+    max_size: u32,
+    padding: u32,
+) -> (Vec<Placement>, Vec<(u32, u32)>) {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let mut pages: Vec<Page> = Vec::new();
+    let mut placements = vec![
+        Placement {
+            page: 0,
+            x: 0,
+            y: 0
+        };
+        sizes.len()
+    ];
+
+    for i in order {
+        let (width, height) = (sizes[i].0 + padding * 2, sizes[i].1 + padding * 2);
+
+        if width > max_size || height > max_size {
+            placements[i] = Placement {
+                page: pages.len(),
+                x: 0,
+                y: 0,
+            };
+            pages.push(Page {
+                shelves: Vec::new(),
+                width,
+                height,
+            });
+            continue;
+        }
+
+        let placed = pages.iter_mut().enumerate().find_map(|(index, page)| {
+            if let Some(shelf) = page
+                .shelves
+                .iter_mut()
+                .find(|shelf| height <= shelf.height && shelf.width + width <= max_size)
+            {
+                let x = shelf.width;
+                shelf.width += width;
+                page.width = page.width.max(shelf.width);
+
+                return Some(Placement {
+                    page: index,
+                    x,
+                    y: shelf.y,
+                });
+            }
+
+            // Only pages built from shelves can grow; oversized pages have none.
+            let top = page.shelves.last().map(|shelf| shelf.y + shelf.height)?;
+            if top + height > max_size {
+                return None;
+            }
+
+            page.shelves.push(Shelf {
+                y: top,
+                height,
+                width,
+            });
+            page.width = page.width.max(width);
+            page.height = top + height;
+
+            Some(Placement {
+                page: index,
+                x: 0,
+                y: top,
+            })
+        });
+
+        placements[i] = placed.unwrap_or_else(|| {
+            pages.push(Page {
+                shelves: vec![Shelf {
+                    y: 0,
+                    height,
+                    width,
+                }],
+                width,
+                height,
+            });
+
+            Placement {
+                page: pages.len() - 1,
+                x: 0,
+                y: 0,
+            }
+        });
+    }
+
+    let pages = pages.iter().map(|page| (page.width, page.height)).collect();
+
+    (placements, pages)
+}
+
+/// Copies `image` onto `page` at `(x, y)` (the padded cell), repeating its edge pixels
+/// `padding` times outwards so filtering never bleeds in neighbouring regions.
+pub fn blit_extruded(
+    page: &mut image::RgbaImage,
+    image: &image::RgbaImage,
+    x: u32,
+    y: u32,
+    padding: u32,
+) {
+    let (width, height) = image.dimensions();
+    let padding = padding as i64;
+
+    // An empty image has no edge pixels to extrude.
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    for dy in -padding..height as i64 + padding {
+        for dx in -padding..width as i64 + padding {
+            let source = image.get_pixel(
+                dx.clamp(0, width as i64 - 1) as u32,
+                dy.clamp(0, height as i64 - 1) as u32,
+            );
+
+            page.put_pixel(
+                (x as i64 + padding + dx) as u32,
+                (y as i64 + padding + dy) as u32,
+                *source,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blit_extruded, pack};
+
+    fn positions(sizes: &[(u32, u32)], max_size: u32, padding: u32) -> Vec<(usize, u32, u32)> {
+        pack(sizes, max_size, padding)
+            .0
+            .iter()
+            .map(|x| (x.page, x.x, x.y))
+            .collect()
+    }
+
+    #[test]
+    fn pack_places_tallest_first_and_returns_input_order() {
+        let sizes = [(10, 5), (10, 20), (10, 10)];
+
+        assert_eq!(
+            positions(&sizes, 64, 0),
+            vec![(0, 20, 0), (0, 0, 0), (0, 10, 0)]
+        );
+        assert_eq!(pack(&sizes, 64, 0).1, vec![(30, 20)]);
+    }
+
+    #[test]
+    fn pack_starts_a_new_shelf_when_a_row_is_full() {
+        assert_eq!(
+            positions(&[(20, 10), (20, 10)], 32, 0),
+            vec![(0, 0, 0), (0, 0, 10)]
+        );
+    }
+
+    #[test]
+    fn pack_starts_a_new_page_when_a_page_is_full() {
+        let (placements, pages) = pack(&[(16, 16), (16, 16)], 16, 0);
+
+        assert_eq!(placements[0].page, 0);
+        assert_eq!(placements[1].page, 1);
+        assert_eq!(pages, vec![(16, 16), (16, 16)]);
+    }
+
+    #[test]
+    fn pack_gives_oversized_rectangles_their_own_page() {
+        let (placements, pages) = pack(&[(8, 8), (40, 10), (8, 8)], 32, 0);
+
+        // It is placed first as the tallest, and no other rectangle can share its page.
+        assert_eq!(placements[1].page, 0);
+        assert_eq!((placements[0].page, placements[2].page), (1, 1));
+        assert_eq!(pages, vec![(40, 10), (16, 8)]);
+    }
+
+    #[test]
+    fn pack_pads_every_cell() {
+        let (placements, pages) = pack(&[(4, 4), (4, 4)], 64, 1);
+
+        assert_eq!((placements[0].x, placements[1].x), (0, 6));
+        assert_eq!(pages, vec![(12, 6)]);
+    }
+
+    #[test]
+    fn blit_extruded_repeats_edge_pixels() {
+        let (red, blue) = (image::Rgba([255, 0, 0, 255]), image::Rgba([0, 0, 255, 255]));
+        let mut source = image::RgbaImage::new(2, 1);
+        source.put_pixel(0, 0, red);
+        source.put_pixel(1, 0, blue);
+
+        let mut page = image::RgbaImage::new(4, 3);
+        blit_extruded(&mut page, &source, 0, 0, 1);
+
+        for y in 0..3 {
+            let row: Vec<_> = (0..4).map(|x| *page.get_pixel(x, y)).collect();
+            assert_eq!(row, vec![red, red, blue, blue]);
+        }
+    }
+
+    #[test]
+    fn blit_extruded_skips_empty_images() {
+        let mut page = image::RgbaImage::new(2, 2);
+        blit_extruded(&mut page, &image::RgbaImage::new(0, 0), 0, 0, 1);
+
+        assert!(page.pixels().all(|x| x.0 == [0, 0, 0, 0]));
+    }
+}
@@ -0,0 +1,171 @@
+use image::{imageops, RgbaImage};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::{
+    assets::{texture::Texture, Assets},
+    math::colors::Color,
+};
+
+/// An image held in CPU memory, for editing pixels before they are uploaded as a texture.
+#[pyclass(name = "Image")]
+#[derive(Debug, Clone)]
+pub struct Bitmap {
+    pub rgba: RgbaImage,
+}
+
+impl Bitmap {
+    pub fn check_bounds(&self, x: u32, y: u32) -> PyResult<()> {
+        if x >= self.rgba.width() || y >= self.rgba.height() {
+            return Err(PyValueError::new_err(format!(
+                "Pixel ({x}, {y}) is outside of the {}x{} image",
+                self.rgba.width(),
+                self.rgba.height()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Bitmap {
+    #[new]
+    #[pyo3(signature = (width, height, color=None))]
+    pub fn new(width: u32, height: u32, color: Option<Color>) -> Self {
+        let color = color.unwrap_or(Color::TRANSPARENT).to_rgba8();
+
+        Self {
+            rgba: RgbaImage::from_pixel(width, height, image::Rgba(color)),
+        }
+    }
+
+    /// Reads an image file from disk. Use `assets.image` to load through the asset mounts.
+    #[staticmethod]
+    pub fn load(path: &str) -> PyResult<Self> {
+        let image = image::open(path).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+        Ok(Self {
+            rgba: image.to_rgba8(),
+        })
+    }
+
+    /// Writes the image to disk, in the format given by the extension of `path`.
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        self.rgba
+            .save(path)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    pub fn get_width(&self) -> u32 {
+        self.rgba.width()
+    }
+
+    #[getter]
+    pub fn get_height(&self) -> u32 {
+        self.rgba.height()
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> PyResult<Color> {
+        self.check_bounds(x, y)?;
+
+        let [r, g, b, a] = self.rgba.get_pixel(x, y).0.map(|x| x as f32 / 255.0);
+        Ok(Color::from_rgba(r, g, b, a))
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) -> PyResult<()> {
+        self.check_bounds(x, y)?;
+
+        self.rgba.put_pixel(x, y, image::Rgba(color.to_rgba8()));
+        Ok(())
+    }
+
+    /// A copy of the rectangle at `(x, y)`, clipped to the image.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            rgba: imageops::crop_imm(&self.rgba, x, y, width, height).to_image(),
+        }
+    }
+
+    /// A copy scaled to `width` by `height`, with `filter` either "nearest" or "bilinear".
+    #[pyo3(signature = (width, height, filter=None))]
+    pub fn resize(&self, width: u32, height: u32, filter: Option<&str>) -> PyResult<Self> {
+        let filter = match filter.unwrap_or("nearest") {
+            "nearest" => imageops::FilterType::Nearest,
+            "bilinear" => imageops::FilterType::Triangle,
+            filter => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown filter '{filter}', expected 'nearest' or 'bilinear'"
+                )))
+            }
+        };
+
+        Ok(Self {
+            rgba: imageops::resize(&self.rgba, width, height, filter),
+        })
+    }
+
+    pub fn flip_horizontal(&self) -> Self {
+        Self {
+            rgba: imageops::flip_horizontal(&self.rgba),
+        }
+    }
+
+    pub fn flip_vertical(&self) -> Self {
+        Self {
+            rgba: imageops::flip_vertical(&self.rgba),
+        }
+    }
+
+    /// A copy rotated clockwise by `turns` quarter turns. Negative turns rotate the other way.
+    #[pyo3(signature = (turns=1))]
+    pub fn rotate90(&self, turns: i32) -> Self {
+        let rgba = match turns.rem_euclid(4) {
+            1 => imageops::rotate90(&self.rgba),
+            2 => imageops::rotate180(&self.rgba),
+            3 => imageops::rotate270(&self.rgba),
+            _ => self.rgba.clone(),
+        };
+
+        Self { rgba }
+    }
+
+    /// A copy with every pixel multiplied by `color`.
+    pub fn tint(&self, color: Color) -> Self {
+        let tint = color.as_array();
+
+        let mut rgba = self.rgba.clone();
+        for pixel in rgba.pixels_mut() {
+            for (channel, tint) in pixel.0.iter_mut().zip(tint) {
+                *channel = (*channel as f32 * tint.clamp(0.0, 1.0)).round() as u8;
+            }
+        }
+
+        Self { rgba }
+    }
+
+    /// Draws `image` onto this one with its top left corner at `(x, y)`, alpha blending unless
+    /// `blend` is false, in which case its pixels are copied as they are.
+    #[pyo3(signature = (image, x, y, blend=true))]
+    pub fn blit(&mut self, image: &Self, x: i64, y: i64, blend: bool) {
+        if blend {
+            imageops::overlay(&mut self.rgba, &image.rgba, x, y);
+        } else {
+            imageops::replace(&mut self.rgba, &image.rgba, x, y);
+        }
+    }
+
+    /// Uploads the image as a new texture.
+    #[pyo3(signature = (mipmaps=false))]
+    pub fn to_texture(&self, py: Python, mipmaps: bool) -> PyResult<Texture> {
+        let assets = py.import("mink")?.getattr("assets")?;
+        let assets = assets
+            .downcast::<Assets>()
+            .map_err(|_| PyRuntimeError::new_err("Textures can only be created while running"))?;
+
+        assets.borrow().texture_from_image(self, mipmaps)
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use pyo3::{
+    prelude::*,
+    types::{PyWeakrefMethods, PyWeakrefReference},
+    PyClass,
+};
+
+/// Loaded assets keyed by resolved path. Entries are weak, so an asset is freed once Python
+/// drops its last reference, and loading it again afterwards reads it from disk anew.
+pub struct AssetCache {
+    pub entries: HashMap<String, Py<PyWeakrefReference>>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get<'py, T>(&self, py: Python<'py>, key: &str) -> Option<Bound<'py, T>>
+    where
+        T: PyClass,
+    {
+        self.entries
+            .get(key)
+            .and_then(|entry| entry.bind(py).upgrade_as::<T>().ok().flatten())
+    }
+
+    pub fn insert<'py, T>(
+        &mut self,
+        py: Python<'py>,
+        key: &str,
+        asset: T,
+    ) -> PyResult<Bound<'py, T>>
+    where
+        T: PyClass + Into<PyClassInitializer<T>>,
+    {
+        let asset = Bound::new(py, asset)?;
+
+        self.prune(py);
+        self.entries.insert(
+            key.to_string(),
+            PyWeakrefReference::new(asset.as_any())?.unbind(),
+        );
+
+        Ok(asset)
+    }
+
+    /// Forgets entries whose assets have already been freed.
+    pub fn prune(&mut self, py: Python) {
+        self.entries
+            .retain(|_, entry| entry.bind(py).upgrade().is_some());
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.entries.remove(key).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,262 @@
+use std::path::Path;
+
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+/// File formats holding textures already encoded for the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    Ktx2,
+    Dds,
+}
+
+impl Container {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "ktx2" => Some(Self::Ktx2),
+            "dds" => Some(Self::Dds),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &str) -> Option<Self> {
+        Self::from_extension(Path::new(path).extension()?.to_str()?)
+    }
+}
+
+/// A texture read from a KTX2 or DDS file, with its mip levels still in their GPU format.
+pub struct CompressedImage {
+    pub format: TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// The data of each mip level, largest first.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    pub fn parse(container: Container, data: &[u8]) -> Result<Self, String> {
+        let image = match container {
+            Container::Ktx2 => Self::parse_ktx2(data)?,
+            Container::Dds => Self::parse_dds(data)?,
+        };
+
+        if image.levels.is_empty() || image.width == 0 || image.height == 0 {
+            return Err("Texture has no data".into());
+        }
+
+        Ok(image)
+    }
+
+    fn parse_ktx2(data: &[u8]) -> Result<Self, String> {
+        use ktx2::Format;
+
+        let reader = ktx2::Reader::new(data).map_err(|e| e.to_string())?;
+        let header = reader.header();
+
+        if header.supercompression_scheme.is_some() {
+            return Err("Supercompressed KTX2 textures are not supported".into());
+        }
+
+        let format = match header.format {
+            Some(Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB) => TextureFormat::Rgba8Unorm,
+            Some(Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB) => TextureFormat::Bgra8Unorm,
+            Some(Format::BC1_RGBA_UNORM_BLOCK | Format::BC1_RGBA_SRGB_BLOCK) => {
+                TextureFormat::Bc1RgbaUnorm
+            }
+            Some(Format::BC2_UNORM_BLOCK | Format::BC2_SRGB_BLOCK) => TextureFormat::Bc2RgbaUnorm,
+            Some(Format::BC3_UNORM_BLOCK | Format::BC3_SRGB_BLOCK) => TextureFormat::Bc3RgbaUnorm,
+            Some(Format::BC4_UNORM_BLOCK) => TextureFormat::Bc4RUnorm,
+            Some(Format::BC5_UNORM_BLOCK) => TextureFormat::Bc5RgUnorm,
+            Some(Format::BC7_UNORM_BLOCK | Format::BC7_SRGB_BLOCK) => TextureFormat::Bc7RgbaUnorm,
+            Some(Format::ETC2_R8G8B8_UNORM_BLOCK | Format::ETC2_R8G8B8_SRGB_BLOCK) => {
+                TextureFormat::Etc2Rgb8Unorm
+            }
+            Some(Format::ETC2_R8G8B8A1_UNORM_BLOCK | Format::ETC2_R8G8B8A1_SRGB_BLOCK) => {
+                TextureFormat::Etc2Rgb8A1Unorm
+            }
+            Some(Format::ETC2_R8G8B8A8_UNORM_BLOCK | Format::ETC2_R8G8B8A8_SRGB_BLOCK) => {
+                TextureFormat::Etc2Rgba8Unorm
+            }
+            Some(Format::ASTC_4x4_UNORM_BLOCK | Format::ASTC_4x4_SRGB_BLOCK) => {
+                astc(AstcBlock::B4x4)
+            }
+            Some(Format::ASTC_5x5_UNORM_BLOCK | Format::ASTC_5x5_SRGB_BLOCK) => {
+                astc(AstcBlock::B5x5)
+            }
+            Some(Format::ASTC_6x6_UNORM_BLOCK | Format::ASTC_6x6_SRGB_BLOCK) => {
+                astc(AstcBlock::B6x6)
+            }
+            Some(Format::ASTC_8x8_UNORM_BLOCK | Format::ASTC_8x8_SRGB_BLOCK) => {
+                astc(AstcBlock::B8x8)
+            }
+            format => return Err(format!("Unsupported KTX2 format {format:?}")),
+        };
+
+        // Only the first layer and face of each level is used.
+        let levels = reader
+            .levels()
+            .enumerate()
+            .map(|(level, data)| {
+                let size = level_size(format, header.pixel_width, header.pixel_height, level);
+                data.data.get(..size).map(|x| x.to_vec())
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or("KTX2 level data is truncated")?;
+
+        Ok(Self {
+            format,
+            width: header.pixel_width,
+            height: header.pixel_height.max(1),
+            levels,
+        })
+    }
+
+    fn parse_dds(data: &[u8]) -> Result<Self, String> {
+        use ddsfile::{D3DFormat, DxgiFormat};
+
+        let dds = ddsfile::Dds::read(data).map_err(|e| e.to_string())?;
+
+        let format = match (dds.get_dxgi_format(), dds.get_d3d_format()) {
+            (Some(DxgiFormat::R8G8B8A8_UNorm | DxgiFormat::R8G8B8A8_UNorm_sRGB), _)
+            | (_, Some(D3DFormat::A8B8G8R8)) => TextureFormat::Rgba8Unorm,
+            (Some(DxgiFormat::B8G8R8A8_UNorm | DxgiFormat::B8G8R8A8_UNorm_sRGB), _)
+            | (_, Some(D3DFormat::A8R8G8B8)) => TextureFormat::Bgra8Unorm,
+            (Some(DxgiFormat::BC1_UNorm | DxgiFormat::BC1_UNorm_sRGB), _)
+            | (_, Some(D3DFormat::DXT1)) => TextureFormat::Bc1RgbaUnorm,
+            (Some(DxgiFormat::BC2_UNorm | DxgiFormat::BC2_UNorm_sRGB), _)
+            | (_, Some(D3DFormat::DXT3)) => TextureFormat::Bc2RgbaUnorm,
+            (Some(DxgiFormat::BC3_UNorm | DxgiFormat::BC3_UNorm_sRGB), _)
+            | (_, Some(D3DFormat::DXT5)) => TextureFormat::Bc3RgbaUnorm,
+            (Some(DxgiFormat::BC4_UNorm), _) => TextureFormat::Bc4RUnorm,
+            (Some(DxgiFormat::BC5_UNorm), _) => TextureFormat::Bc5RgUnorm,
+            (Some(DxgiFormat::BC7_UNorm | DxgiFormat::BC7_UNorm_sRGB), _) => {
+                TextureFormat::Bc7RgbaUnorm
+            }
+            (dxgi, d3d) => return Err(format!("Unsupported DDS format {dxgi:?} {d3d:?}")),
+        };
+
+        let (width, height) = (dds.get_width(), dds.get_height());
+        let mut data = dds.get_data(0).map_err(|e| e.to_string())?;
+
+        // The levels of a layer are stored back to back.
+        let mut levels = Vec::new();
+        for level in 0..dds.get_num_mipmap_levels().max(1) as usize {
+            let size = level_size(format, width, height, level);
+            if data.len() < size {
+                return Err("DDS level data is truncated".into());
+            }
+
+            let (level, rest) = data.split_at(size);
+            levels.push(level.to_vec());
+            data = rest;
+        }
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            levels,
+        })
+    }
+
+    /// Whether the GPU can sample this texture as it is, without decoding it first.
+    pub fn is_supported(&self, device: &wgpu::Device) -> bool {
+        let (block_width, block_height) = self.format.block_dimensions();
+
+        // Compressed textures have to be made of whole blocks.
+        self.format.is_compressed()
+            && device.features().contains(self.format.required_features())
+            && self.width.is_multiple_of(block_width)
+            && self.height.is_multiple_of(block_height)
+    }
+
+    /// Decodes the largest level to RGBA on the CPU, for GPUs lacking support for its format.
+    pub fn decode(&self) -> Result<image::RgbaImage, String> {
+        use texture2ddecoder as decoder;
+
+        let (width, height) = (self.width as usize, self.height as usize);
+        let data = &self.levels[0];
+
+        let rgba = match self.format {
+            TextureFormat::Rgba8Unorm => data.clone(),
+            TextureFormat::Bgra8Unorm => data
+                .chunks_exact(4)
+                .flat_map(|x| [x[2], x[1], x[0], x[3]])
+                .collect(),
+            format => {
+                let mut pixels = vec![0; width * height];
+
+                match format {
+                    TextureFormat::Bc1RgbaUnorm => {
+                        decoder::decode_bc1a(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Bc2RgbaUnorm => {
+                        decoder::decode_bc2(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Bc3RgbaUnorm => {
+                        decoder::decode_bc3(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Bc4RUnorm => {
+                        decoder::decode_bc4(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Bc5RgUnorm => {
+                        decoder::decode_bc5(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Bc7RgbaUnorm => {
+                        decoder::decode_bc7(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Etc2Rgb8Unorm => {
+                        decoder::decode_etc2_rgb(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Etc2Rgb8A1Unorm => {
+                        decoder::decode_etc2_rgba1(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Etc2Rgba8Unorm => {
+                        decoder::decode_etc2_rgba8(data, width, height, &mut pixels)
+                    }
+                    TextureFormat::Astc { .. } => {
+                        let (block_width, block_height) = format.block_dimensions();
+                        decoder::decode_astc(
+                            data,
+                            width,
+                            height,
+                            block_width as usize,
+                            block_height as usize,
+                            &mut pixels,
+                        )
+                    }
+                    format => return Err(format!("Can't decode {format:?}")),
+                }?;
+
+                // The decoder packs pixels as little endian BGRA.
+                pixels
+                    .into_iter()
+                    .flat_map(|x| {
+                        let [b, g, r, a] = x.to_le_bytes();
+                        [r, g, b, a]
+                    })
+                    .collect()
+            }
+        };
+
+        image::RgbaImage::from_raw(self.width, self.height, rgba)
+            .ok_or_else(|| "Texture data is truncated".into())
+    }
+}
+
+fn astc(block: AstcBlock) -> TextureFormat {
+    TextureFormat::Astc {
+        block,
+        channel: AstcChannel::Unorm,
+    }
+}
+
+/// The size in bytes of mip `level` of a `width` by `height` texture in `format`.
+pub fn level_size(format: TextureFormat, width: u32, height: u32, level: usize) -> usize {
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).unwrap_or(4);
+
+    let width = (width >> level).max(1);
+    let height = (height >> level).max(1);
+
+    (width.div_ceil(block_width) * height.div_ceil(block_height) * block_size) as usize
+}
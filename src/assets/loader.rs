@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    panic::AssertUnwindSafe,
+    path::Path,
+    sync::{mpsc::Receiver, Arc, Mutex},
+};
+
+use kira::sound::static_sound::StaticSoundData;
+use pyo3::{exceptions::PyKeyError, prelude::*};
+
+use crate::assets::{
+    compressed::{CompressedImage, Container},
+    vfs::Vfs,
+};
+
+/// An asset decoded on a worker thread, waiting to be finished on the main thread.
+pub enum DecodedAsset {
+    Image(image::RgbaImage),
+    Compressed(CompressedImage),
+    Sound(Box<StaticSoundData>),
+}
+
+impl DecodedAsset {
+    /// Reads and decodes `path`, picking the asset type from its extension.
+    pub fn load(vfs: &Vfs, path: &str) -> Result<Self, String> {
+        let data = vfs.read(path).map_err(|e| e.to_string())?;
+
+        if let Some(container) = Container::from_path(path) {
+            CompressedImage::parse(container, &data).map(Self::Compressed)
+        } else if Self::is_image(path) {
+            decode_image(path, &data).map(Self::Image)
+        } else {
+            decode_sound(data).map(|data| Self::Sound(Box::new(data)))
+        }
+    }
+
+    pub fn is_image(path: &str) -> bool {
+        Container::from_path(path).is_some()
+            || image::ImageFormat::from_path(Path::new(path)).is_ok_and(|x| x.reading_enabled())
+    }
+}
+
+/// Decodes an image, using the extension of `path` as a hint for its format.
+pub fn decode_image(path: &str, data: &[u8]) -> Result<image::RgbaImage, String> {
+    decode_image_as(data, image::ImageFormat::from_path(path).ok())
+}
+
+/// Decodes any supported image to RGBA, including GPU-compressed ones.
+pub fn decode_rgba(path: &str, data: &[u8]) -> Result<image::RgbaImage, String> {
+    match Container::from_path(path) {
+        Some(container) => CompressedImage::parse(container, data)?.decode(),
+        None => decode_image(path, data),
+    }
+}
+
+/// Decodes an image in `format`, or whatever format its contents look like if not given.
+pub fn decode_image_as(
+    data: &[u8],
+    format: Option<image::ImageFormat>,
+) -> Result<image::RgbaImage, String> {
+    let image = match format {
+        Some(format) => image::load_from_memory_with_format(data, format),
+        None => image::load_from_memory(data),
+    };
+
+    image.map(|x| x.to_rgba8()).map_err(|e| e.to_string())
+}
+
+/// Decodes every frame of an animated GIF, WebP or PNG, with how long each is shown in seconds.
+/// Frames are composited onto the full canvas, so each one can be drawn on its own.
+pub fn decode_frames(path: &str, data: &[u8]) -> Result<Vec<(image::RgbaImage, f32)>, String> {
+    use image::{
+        codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+        AnimationDecoder, ImageFormat,
+    };
+
+    let data = Cursor::new(data);
+    let frames = match ImageFormat::from_path(path) {
+        Ok(ImageFormat::Gif) => GifDecoder::new(data).map(|x| x.into_frames()),
+        Ok(ImageFormat::WebP) => WebPDecoder::new(data).map(|x| x.into_frames()),
+        Ok(ImageFormat::Png) => {
+            PngDecoder::new(data).and_then(|x| x.apng().map(|x| x.into_frames()))
+        }
+        _ => return Err(format!("'{path}' is not a GIF, WebP or PNG file")),
+    };
+
+    frames
+        .and_then(|x| x.collect_frames())
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|frame| {
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            let seconds = numerator as f32 / denominator as f32 / 1000.0;
+
+            Ok((frame.into_buffer(), seconds))
+        })
+        .collect()
+}
+
+pub fn decode_sound(data: Vec<u8>) -> Result<StaticSoundData, String> {
+    StaticSoundData::from_cursor(Cursor::new(data)).map_err(|e| e.to_string())
+}
+
+pub struct LoadGroupState {
+    pub total: usize,
+    pub assets: HashMap<String, PyObject>,
+    pub errors: HashMap<String, String>,
+}
+
+impl LoadGroupState {
+    pub fn finished(&self) -> usize {
+        self.assets.len() + self.errors.len()
+    }
+}
+
+/// A group of assets being loaded in the background.
+#[pyclass]
+pub struct LoadGroup {
+    pub state: Arc<Mutex<LoadGroupState>>,
+}
+
+#[pymethods]
+impl LoadGroup {
+    #[getter]
+    pub fn get_progress(&self) -> f32 {
+        let state = self.state.lock().unwrap();
+
+        if state.total == 0 {
+            1.0
+        } else {
+            state.finished() as f32 / state.total as f32
+        }
+    }
+
+    #[getter]
+    pub fn get_done(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.finished() == state.total
+    }
+
+    #[getter]
+    pub fn get_errors(&self) -> HashMap<String, String> {
+        self.state.lock().unwrap().errors.clone()
+    }
+
+    pub fn get(&self, py: Python, path: &str) -> Option<PyObject> {
+        self.state
+            .lock()
+            .unwrap()
+            .assets
+            .get(path)
+            .map(|x| x.clone_ref(py))
+    }
+
+    pub fn __getitem__(&self, py: Python, path: &str) -> PyResult<PyObject> {
+        self.get(py, path)
+            .ok_or_else(|| PyKeyError::new_err(format!("Asset '{path}' is not loaded")))
+    }
+}
+
+/// The main thread's side of a [`LoadGroup`], receiving assets as workers decode them.
+pub struct PendingLoad {
+    pub receiver: Mutex<Receiver<(String, Result<DecodedAsset, String>)>>,
+    pub state: Arc<Mutex<LoadGroupState>>,
+}
+
+impl PendingLoad {
+    /// Reads and decodes every path in `paths` on the rayon thread pool.
+    pub fn spawn(paths: Vec<String>, vfs: Arc<Vfs>, state: Arc<Mutex<LoadGroupState>>) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        for path in paths {
+            let sender = sender.clone();
+            let vfs = Arc::clone(&vfs);
+
+            rayon::spawn(move || {
+                // A panic on the pool would abort the game, so a decoder panicking on a bad
+                // file fails that asset instead.
+                let result =
+                    std::panic::catch_unwind(AssertUnwindSafe(|| DecodedAsset::load(&vfs, &path)))
+                        .unwrap_or_else(|panic| {
+                            let message = panic
+                                .downcast_ref::<&str>()
+                                .map(|x| x.to_string())
+                                .or_else(|| panic.downcast_ref::<String>().cloned())
+                                .unwrap_or_else(|| "unknown error".to_string());
+
+                            Err(format!("Failed to decode '{path}': {message}"))
+                        });
+                let _ = sender.send((path, result));
+            });
+        }
+
+        Self {
+            receiver: Mutex::new(receiver),
+            state,
+        }
+    }
+}
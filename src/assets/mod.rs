@@ -1,24 +1,62 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
-use kira::sound::static_sound::StaticSoundData;
-use pyo3::prelude::*;
+use kira::Frame;
+use pyo3::{
+    buffer::PyBuffer,
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
 
 use crate::{
-    assets::{music::Music, sound::Sound, texture::Texture},
-    graphics::{builtin::VideoBuiltins, stack::VideoStack},
+    assets::{
+        atlas::Atlas,
+        bitmap::Bitmap,
+        cache::AssetCache,
+        compressed::{CompressedImage, Container},
+        loader::{
+            decode_frames, decode_image, decode_image_as, decode_rgba, decode_sound, DecodedAsset,
+            LoadGroup, LoadGroupState, PendingLoad,
+        },
+        music::{Music, MusicSource},
+        sound::Sound,
+        texture::Texture,
+        vfs::{Mount, MountSource, Vfs},
+        watcher::Watcher,
+    },
+    graphics::{builtin::VideoBuiltins, mipmaps::MipmapGenerator, stack::VideoStack},
 };
 
 pub mod api;
+pub mod atlas;
+pub mod bitmap;
+pub mod cache;
+pub mod compressed;
+pub mod loader;
 pub mod music;
 pub mod sound;
 pub mod texture;
+pub mod vfs;
+pub mod watcher;
+
+type ReloadFn = fn(&Assets, Python, &str) -> Result<(), String>;
 
 #[pyclass]
 pub struct Assets {
     pub device: Arc<wgpu::Device>,
     pub queue: Arc<wgpu::Queue>,
     pub builtins: Arc<VideoBuiltins>,
-    pub root: String,
+    pub vfs: Arc<Vfs>,
+    pub atlases: usize,
+    pub textures: AssetCache,
+    pub sounds: AssetCache,
+    pub music: AssetCache,
+    pub pending: Vec<PendingLoad>,
+    pub watcher: Option<Watcher>,
 }
 
 impl Assets {
@@ -27,75 +65,316 @@ impl Assets {
             device: Arc::clone(&video.device),
             queue: Arc::clone(&video.queue),
             builtins,
-            root: "assets".to_string(),
+            vfs: Arc::new(Vfs::new("assets")),
+            atlases: 0,
+            textures: AssetCache::new(),
+            sounds: AssetCache::new(),
+            music: AssetCache::new(),
+            pending: Vec::new(),
+            watcher: None,
         }
     }
-}
 
-#[pymethods]
-impl Assets {
-    pub fn set_root(&mut self, path: &str) {
-        self.root = path.to_string();
+    /// Finishes assets decoded in the background since the last call. Textures are uploaded
+    /// here, as GPU work has to happen on the main thread.
+    pub fn poll(&mut self, py: Python) {
+        let mut pending = std::mem::take(&mut self.pending);
+
+        for load in &pending {
+            let receiver = load.receiver.lock().unwrap();
+            while let Ok((path, result)) = receiver.try_recv() {
+                let key = vfs::normalize(&path);
+
+                let asset = match result {
+                    Ok(DecodedAsset::Image(rgba)) => self
+                        .create_texture(&path, &rgba, false)
+                        .map_err(PyRuntimeError::new_err)
+                        .and_then(|texture| self.textures.insert(py, &key, texture))
+                        .map(|x| x.into_any().unbind()),
+                    Ok(DecodedAsset::Compressed(image)) => self
+                        .create_compressed_texture(&path, &image, false)
+                        .map_err(PyRuntimeError::new_err)
+                        .and_then(|texture| self.textures.insert(py, &key, texture))
+                        .map(|x| x.into_any().unbind()),
+                    Ok(DecodedAsset::Sound(data)) => self
+                        .sounds
+                        .insert(py, &key, Sound::new(*data))
+                        .map(|x| x.into_any().unbind()),
+                    Err(e) => Err(PyRuntimeError::new_err(e)),
+                };
+
+                let mut state = load.state.lock().unwrap();
+                match asset {
+                    Ok(asset) => {
+                        state.assets.insert(path, asset);
+                    }
+                    Err(e) => {
+                        state.errors.insert(path, e.to_string());
+                    }
+                }
+            }
+        }
+
+        pending.retain(|load| {
+            let state = load.state.lock().unwrap();
+            state.finished() < state.total
+        });
+        pending.append(&mut self.pending);
+        self.pending = pending;
+
+        self.hot_reload(py);
     }
 
-    pub fn resolve_path(&self, path: &str) -> String {
-        format!("{}/{}", self.root, path)
+    /// Reloads cached assets whose files changed on disk, in place, if watching is enabled.
+    /// Failed reloads keep the previous data and are reported instead of raised.
+    pub fn hot_reload(&mut self, py: Python) {
+        let mut watcher = match self.watcher.take() {
+            Some(watcher) => watcher,
+            None => return,
+        };
+
+        if watcher.due() {
+            let kinds: [(Vec<String>, ReloadFn); 3] = [
+                (
+                    self.textures.entries.keys().cloned().collect(),
+                    Self::reload_texture,
+                ),
+                (
+                    self.sounds.entries.keys().cloned().collect(),
+                    Self::reload_sound,
+                ),
+                (
+                    self.music.entries.keys().cloned().collect(),
+                    Self::reload_music,
+                ),
+            ];
+
+            for (paths, reload) in kinds {
+                for path in paths {
+                    // Only files read from a directory can change.
+                    let changed = self
+                        .vfs
+                        .real_path(&path)
+                        .is_some_and(|x| watcher.changed(&x.to_string_lossy()));
+                    if !changed {
+                        continue;
+                    }
+
+                    match reload(self, py, &path) {
+                        Ok(()) => {
+                            watcher.errors.remove(&path);
+                        }
+                        Err(e) => {
+                            watcher.errors.insert(path, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.watcher = Some(watcher);
     }
 
-    pub fn music(&self, path: &str) -> Music {
-        let filepath = self.resolve_path(path);
+    /// Reloads the cached texture at `path` by writing the new image into its GPU texture, so
+    /// every copy of the handle (including those held by batches, texture arrays and atlases)
+    /// sees the change. An image that changed size or format would need a new GPU texture
+    /// those copies wouldn't pick up, so it fails instead and the old image stays.
+    pub fn reload_texture(&self, py: Python, path: &str) -> Result<(), String> {
+        let texture = match self.textures.get::<Texture>(py, path) {
+            Some(texture) => texture,
+            None => return Ok(()),
+        };
+
+        let data = self.vfs.read(path).map_err(|e| e.to_string())?;
+        let texture = texture.try_borrow().map_err(|e| e.to_string())?;
+
+        let rgba = match Container::from_path(path) {
+            Some(container) => {
+                let image = CompressedImage::parse(container, &data)?;
+                Self::check_reload_size(&texture, image.width, image.height)?;
 
-        Music {
-            volume: 1.0,
-            speed: 1.0,
-            r#loop: false,
-            paused: false,
-            data: StaticSoundData::from_file(filepath).expect("Failed to load music"),
-            handle: None,
+                let target = &texture.texture;
+                if image.is_supported(&self.device)
+                    && target.format() == image.format
+                    && target.mip_level_count() == image.levels.len() as u32
+                {
+                    self.write_levels(target, &image);
+                    return Ok(());
+                }
+
+                if texture.check_rgba().is_err() {
+                    return Err(format!(
+                        "Texture '{path}' changed format, restart to see the new image"
+                    ));
+                }
+
+                image.decode()?
+            }
+            None => decode_image(path, &data)?,
+        };
+
+        Self::check_reload_size(&texture, rgba.width(), rgba.height())?;
+        self.write_texture(&texture.texture, &rgba);
+
+        if let Some(mipmaps) = &texture.mipmaps {
+            mipmaps.mark_dirty(&texture.texture);
         }
+
+        Ok(())
     }
 
-    pub fn sound(&self, path: &str) -> Sound {
-        let filepath = self.resolve_path(path);
+    fn check_reload_size(texture: &Texture, width: u32, height: u32) -> Result<(), String> {
+        let (old_width, old_height) = (texture.texture.width(), texture.texture.height());
 
-        Sound {
-            volume: 1.0,
-            speed: 1.0,
-            data: StaticSoundData::from_file(filepath).expect("Failed to load sound"),
+        if (width, height) != (old_width, old_height) {
+            return Err(format!(
+                "Texture '{}' changed size from {old_width}x{old_height} to {width}x{height}, \
+                 restart to see the new image",
+                texture.path
+            ));
         }
+
+        Ok(())
     }
 
-    pub fn texture(&self, path: &str) -> Texture {
-        let filepath = self.resolve_path(path);
+    pub fn reload_sound(&self, py: Python, path: &str) -> Result<(), String> {
+        let sound = match self.sounds.get::<Sound>(py, path) {
+            Some(sound) => sound,
+            None => return Ok(()),
+        };
+
+        let data = decode_sound(self.vfs.read(path).map_err(|e| e.to_string())?)?;
+        let mut sound = sound.try_borrow_mut().map_err(|e| e.to_string())?;
+
+        sound.data = data;
+        let (volume, speed) = (sound.volume, sound.speed);
+        sound.set_volume(volume);
+        sound.set_speed(speed);
+
+        Ok(())
+    }
+
+    pub fn reload_music(&self, py: Python, path: &str) -> Result<(), String> {
+        let music = match self.music.get::<Music>(py, path) {
+            Some(music) => music,
+            None => return Ok(()),
+        };
+
+        let source = self.music_source(path).map_err(|e| e.to_string())?;
+        let mut music = music.try_borrow_mut().map_err(|e| e.to_string())?;
+
+        music.set_source(source)
+    }
+
+    /// Streams music straight from disk when it comes from a directory, otherwise from its
+    /// encoded bytes.
+    pub fn music_source(&self, path: &str) -> std::io::Result<MusicSource> {
+        match self.vfs.real_path(path) {
+            Some(path) => Ok(MusicSource::File(path)),
+            None => Ok(MusicSource::Memory(self.vfs.read(path)?.into())),
+        }
+    }
+
+    /// Reads `path` through the mounted sources, panicking if no source has it.
+    pub fn read(&self, path: &str) -> Vec<u8> {
+        self.vfs.read(path).expect("Failed to read asset")
+    }
 
-        let image = image::open(filepath).expect("Failed to load image");
-        let rgba = image.to_rgba8();
+    pub const ATLAS_PADDING: u32 = 2;
+    pub const ATLAS_MAX_SIZE: u32 = 4096;
+
+    /// Packs `images` into as few atlas pages as possible and uploads them. Images too large
+    /// for the GPU, even on a page of their own, are rejected.
+    pub fn build_atlas(
+        &mut self,
+        images: Vec<(String, image::RgbaImage)>,
+        padding: u32,
+    ) -> PyResult<Atlas> {
+        let limit = self.device.limits().max_texture_dimension_2d;
+        let max_size = Self::ATLAS_MAX_SIZE.min(limit);
+
+        if let Some((name, image)) = images.iter().find(|(_, image)| {
+            image.width() + padding * 2 > limit || image.height() + padding * 2 > limit
+        }) {
+            return Err(PyValueError::new_err(format!(
+                "Image '{name}' is {}x{} with padding {padding}, larger than the largest texture \
+                 this GPU supports ({limit}x{limit})",
+                image.width(),
+                image.height()
+            )));
+        }
+
+        let sizes: Vec<(u32, u32)> = images.iter().map(|(_, image)| image.dimensions()).collect();
+        let (placements, page_sizes) = atlas::pack(&sizes, max_size, padding);
+
+        let mut pages: Vec<image::RgbaImage> = page_sizes
+            .iter()
+            .map(|(width, height)| image::RgbaImage::new(*width, *height))
+            .collect();
+
+        for ((_, image), placement) in images.iter().zip(&placements) {
+            atlas::blit_extruded(
+                &mut pages[placement.page],
+                image,
+                placement.x,
+                placement.y,
+                padding,
+            );
+        }
+
+        let id = self.atlases;
+        self.atlases += 1;
+
+        let pages: Vec<Texture> = pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                self.create_texture(&format!("atlas {id}, page {index}"), page, false)
+            })
+            .collect::<Result<_, _>>()
+            .map_err(PyValueError::new_err)?;
+
+        let regions = images
+            .iter()
+            .zip(&placements)
+            .map(|((name, image), placement)| {
+                let page = &pages[placement.page];
+                let (width, height) = image.dimensions();
+
+                let region = Texture {
+                    size: Arc::new(glam::Vec2::new(width as f32, height as f32)),
+                    uv: glam::Vec4::new(
+                        (placement.x + padding) as f32 / page.size.x,
+                        (placement.y + padding) as f32 / page.size.y,
+                        width as f32 / page.size.x,
+                        height as f32 / page.size.y,
+                    ),
+                    ..page.clone()
+                };
+
+                (name.clone(), region)
+            })
+            .collect();
+
+        Ok(Atlas { regions })
+    }
 
+    /// Overwrites the whole of `texture` with `rgba`, which must be the same size.
+    pub fn write_texture(&self, texture: &wgpu::Texture, rgba: &image::RgbaImage) {
         let size = wgpu::Extent3d {
-            width: image.width(),
-            height: image.height(),
+            width: rgba.width(),
+            height: rgba.height(),
             depth_or_array_layers: 1,
         };
 
-        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some(&format!("Texture: {path}")),
-            size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-
         self.queue.write_texture(
             wgpu::TexelCopyTextureInfo {
-                texture: &texture,
+                texture,
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            &rgba,
+            rgba,
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
                 bytes_per_row: Some(4 * size.width),
@@ -103,6 +382,153 @@ impl Assets {
             },
             size,
         );
+    }
+
+    /// Checks a `width` by `height` texture can be created on this GPU.
+    pub fn check_texture_size(&self, width: u32, height: u32) -> Result<(), String> {
+        let limit = self.device.limits().max_texture_dimension_2d;
+
+        if !(1..=limit).contains(&width) || !(1..=limit).contains(&height) {
+            return Err(format!(
+                "Expected a texture from 1x1 to {limit}x{limit} pixels, got {width}x{height}"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Uploads `rgba` to a new GPU texture, labelled with `path`. With `mipmaps`, the texture
+    /// gets a full mip chain, generated from `rgba` on the GPU.
+    pub fn create_texture(
+        &self,
+        path: &str,
+        rgba: &image::RgbaImage,
+        mipmaps: bool,
+    ) -> Result<Texture, String> {
+        self.check_texture_size(rgba.width(), rgba.height())?;
+
+        let size = wgpu::Extent3d {
+            width: rgba.width(),
+            height: rgba.height(),
+            depth_or_array_layers: 1,
+        };
+
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC;
+        let mut mip_level_count = 1;
+
+        if mipmaps {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+            mip_level_count = MipmapGenerator::level_count(size.width, size.height);
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&format!("Texture: {path}")),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: MipmapGenerator::FORMAT,
+            usage,
+            view_formats: &[],
+        });
+
+        self.write_texture(&texture, rgba);
+
+        let mipmaps = (mip_level_count > 1).then(|| Arc::clone(&self.builtins.mipmaps));
+        if let Some(mipmaps) = &mipmaps {
+            mipmaps.generate(&texture);
+        }
+
+        Ok(self.bind_texture(path, texture, mipmaps))
+    }
+
+    /// Uploads a KTX2 or DDS texture in its own format if the GPU supports it, decoding it to
+    /// RGBA first otherwise. The file's mip levels are used when uploaded as they are.
+    pub fn create_compressed_texture(
+        &self,
+        path: &str,
+        image: &CompressedImage,
+        mipmaps: bool,
+    ) -> Result<Texture, String> {
+        if !image.is_supported(&self.device) {
+            let rgba = image.decode()?;
+            return self.create_texture(path, &rgba, mipmaps || image.levels.len() > 1);
+        }
+
+        self.check_texture_size(image.width, image.height)?;
+
+        let size = wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&format!("Texture: {path}")),
+            size,
+            mip_level_count: image.levels.len() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: image.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        self.write_levels(&texture, image);
+
+        Ok(self.bind_texture(path, texture, None))
+    }
+
+    /// Uploads every mip level of `image` into `texture`, which has the same size and format.
+    pub fn write_levels(&self, texture: &wgpu::Texture, image: &CompressedImage) {
+        let (block_width, block_height) = image.format.block_dimensions();
+        let block_size = image.format.block_copy_size(None).unwrap_or(4);
+
+        for (level, data) in image.levels.iter().enumerate() {
+            let level_size = texture
+                .size()
+                .mip_level_size(level as u32, wgpu::TextureDimension::D2)
+                .physical_size(image.format);
+
+            self.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(level_size.width / block_width * block_size),
+                    rows_per_image: Some(level_size.height / block_height),
+                },
+                level_size,
+            );
+        }
+    }
+
+    /// Decodes the file `path` held in `data` and uploads it, whatever kind of image it is.
+    pub fn load_texture(&self, path: &str, data: &[u8], mipmaps: bool) -> Result<Texture, String> {
+        match Container::from_path(path) {
+            Some(container) => {
+                let image = CompressedImage::parse(container, data)?;
+                self.create_compressed_texture(path, &image, mipmaps)
+            }
+            None => self.create_texture(path, &decode_image(path, data)?, mipmaps),
+        }
+    }
+
+    /// Wraps `texture` in a handle, with the view and binding used to draw it.
+    pub fn bind_texture(
+        &self,
+        path: &str,
+        texture: wgpu::Texture,
+        mipmaps: Option<Arc<MipmapGenerator>>,
+    ) -> Texture {
+        let size = texture.size();
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
@@ -126,7 +552,405 @@ impl Assets {
             texture: Arc::new(texture),
             view: Arc::new(view),
             binding: Arc::new(binding),
+            device: Arc::clone(&self.device),
+            queue: Arc::clone(&self.queue),
+            mipmaps,
             size: Arc::new([size.width as f32, size.height as f32].into()),
+            uv: Texture::FULL_UV,
+        }
+    }
+}
+
+#[pymethods]
+impl Assets {
+    pub fn set_root(&mut self, path: &str) {
+        self.vfs.set_root(path);
+    }
+
+    #[getter]
+    pub fn get_hot_reload(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Watches the files of loaded assets and reloads them in place when they change. Textures
+    /// whose image changes size or format keep the old image, with the reason listed in
+    /// `reload_errors`.
+    #[setter]
+    pub fn set_hot_reload(&mut self, value: bool) {
+        if value != self.watcher.is_some() {
+            self.watcher = value.then(Watcher::new);
         }
     }
+
+    #[getter]
+    pub fn get_reload_errors(&self) -> HashMap<String, String> {
+        self.watcher
+            .as_ref()
+            .map(|x| x.errors.clone())
+            .unwrap_or_default()
+    }
+
+    /// The file on disk `path` is read from, going through the mounts like loading does. None
+    /// if no source has it, or it comes from an archive or memory.
+    pub fn resolve_path(&self, path: &str) -> Option<String> {
+        self.vfs
+            .real_path(path)
+            .map(|x| x.to_string_lossy().into_owned())
+    }
+
+    /// Makes the files of a directory or .zip archive readable as assets under `point`.
+    /// Mounts with a higher priority are searched first, and the root directory last.
+    #[pyo3(signature = (path, point=None, priority=None))]
+    pub fn mount(&self, path: &str, point: Option<&str>, priority: Option<i32>) -> PyResult<()> {
+        let filepath = Path::new(path);
+
+        let source = if filepath.is_dir() {
+            MountSource::Directory(filepath.to_path_buf())
+        } else {
+            let archive = vfs::open_zip(filepath).map_err(PyRuntimeError::new_err)?;
+            MountSource::Zip(Mutex::new(archive))
+        };
+
+        self.vfs.mount(Mount {
+            name: path.to_string(),
+            point: vfs::normalize(point.unwrap_or("")),
+            priority: priority.unwrap_or(0),
+            source,
+        });
+
+        Ok(())
+    }
+
+    /// Mounts files held in memory, keyed by their path under `point`.
+    #[pyo3(signature = (name, files, point=None, priority=None))]
+    pub fn mount_memory(
+        &self,
+        name: &str,
+        files: HashMap<String, Bound<PyBytes>>,
+        point: Option<&str>,
+        priority: Option<i32>,
+    ) {
+        let files = files
+            .into_iter()
+            .map(|(path, data)| (vfs::normalize(&path), data.as_bytes().to_vec()))
+            .collect();
+
+        self.vfs.mount(Mount {
+            name: name.to_string(),
+            point: vfs::normalize(point.unwrap_or("")),
+            priority: priority.unwrap_or(0),
+            source: MountSource::Memory(files),
+        });
+    }
+
+    /// Removes the mount named `name`, which for directories and archives is their path.
+    pub fn unmount(&self, name: &str) -> bool {
+        self.vfs.unmount(name)
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.vfs.exists(path)
+    }
+
+    pub fn read_bytes<'py>(&self, py: Python<'py>, path: &str) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.vfs.read(path)?))
+    }
+
+    pub fn read_text(&self, path: &str) -> PyResult<String> {
+        String::from_utf8(self.vfs.read(path)?).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    pub fn music<'py>(&mut self, py: Python<'py>, path: &str) -> PyResult<Bound<'py, Music>> {
+        let key = vfs::normalize(path);
+
+        if let Some(music) = self.music.get(py, &key) {
+            return Ok(music);
+        }
+
+        let music = Music::new(self.music_source(path).expect("Failed to read asset"))
+            .expect("Failed to load music");
+
+        self.music.insert(py, &key, music)
+    }
+
+    pub fn sound<'py>(&mut self, py: Python<'py>, path: &str) -> PyResult<Bound<'py, Sound>> {
+        let key = vfs::normalize(path);
+
+        if let Some(sound) = self.sounds.get(py, &key) {
+            return Ok(sound);
+        }
+
+        let sound = Sound::new(decode_sound(self.read(path)).expect("Failed to load sound"));
+
+        self.sounds.insert(py, &key, sound)
+    }
+
+    /// Loads a texture, reusing the cached one unless it was loaded with different `mipmaps`.
+    #[pyo3(signature = (path, mipmaps=false))]
+    pub fn texture<'py>(
+        &mut self,
+        py: Python<'py>,
+        path: &str,
+        mipmaps: bool,
+    ) -> PyResult<Bound<'py, Texture>> {
+        let key = vfs::normalize(path);
+
+        if let Some(texture) = self.textures.get::<Texture>(py, &key) {
+            // Compressed textures keep the mip levels stored in their file.
+            if texture.borrow().mipmaps.is_some() == mipmaps || Container::from_path(path).is_some()
+            {
+                return Ok(texture);
+            }
+        }
+
+        let texture = self
+            .load_texture(path, &self.read(path), mipmaps)
+            .expect("Failed to load image");
+
+        self.textures.insert(py, &key, texture)
+    }
+
+    /// Loads every frame of an animated GIF, WebP or PNG as its own texture, paired with how
+    /// long it should be shown in seconds.
+    pub fn animation(&self, path: &str) -> PyResult<Vec<(Texture, f32)>> {
+        let frames = decode_frames(path, &self.vfs.read(path)?).map_err(PyRuntimeError::new_err)?;
+
+        frames
+            .into_iter()
+            .enumerate()
+            .map(|(index, (rgba, seconds))| {
+                let texture = self
+                    .create_texture(&format!("{path}, frame {index}"), &rgba, false)
+                    .map_err(PyValueError::new_err)?;
+                Ok((texture, seconds))
+            })
+            .collect()
+    }
+
+    /// Decodes an encoded image, such as the contents of a .png or .ktx2 file. The format is
+    /// guessed from the data unless given as a file extension, which KTX2 and DDS data need.
+    #[pyo3(signature = (data, format=None, mipmaps=false))]
+    pub fn texture_from_bytes(
+        &self,
+        data: &[u8],
+        format: Option<&str>,
+        mipmaps: bool,
+    ) -> PyResult<Texture> {
+        if let Some(container) = format.and_then(Container::from_extension) {
+            let image = CompressedImage::parse(container, data).map_err(PyRuntimeError::new_err)?;
+            self.check_texture_size(image.width, image.height)
+                .map_err(PyValueError::new_err)?;
+
+            return self
+                .create_compressed_texture("bytes", &image, mipmaps)
+                .map_err(PyRuntimeError::new_err);
+        }
+
+        let format = match format {
+            Some(format) => Some(image::ImageFormat::from_extension(format).ok_or_else(|| {
+                PyValueError::new_err(format!("Unknown image format '{format}'"))
+            })?),
+            None => None,
+        };
+
+        let image = decode_image_as(data, format).map_err(PyRuntimeError::new_err)?;
+
+        self.create_texture("bytes", &image, mipmaps)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Creates a texture from raw pixels, four bytes (red, green, blue, alpha) each, row by row.
+    #[pyo3(signature = (width, height, rgba, mipmaps=false))]
+    pub fn texture_from_pixels(
+        &self,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+        mipmaps: bool,
+    ) -> PyResult<Texture> {
+        let image = image::RgbaImage::from_raw(width, height, rgba.to_vec()).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Expected {} bytes for a {width}x{height} texture",
+                4 * width as usize * height as usize
+            ))
+        })?;
+
+        self.create_texture("pixels", &image, mipmaps)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Uploads a copy of `image` as a new texture.
+    #[pyo3(signature = (image, mipmaps=false))]
+    pub fn texture_from_image(&self, image: &Bitmap, mipmaps: bool) -> PyResult<Texture> {
+        self.create_texture("image", &image.rgba, mipmaps)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Decodes an image into CPU memory without uploading it.
+    pub fn image(&self, path: &str) -> PyResult<Bitmap> {
+        let rgba = decode_rgba(path, &self.vfs.read(path)?).map_err(PyRuntimeError::new_err)?;
+
+        Ok(Bitmap { rgba })
+    }
+
+    /// Creates a transparent texture to be drawn into with `set_pixels`.
+    #[pyo3(signature = (width, height, mipmaps=false))]
+    pub fn blank_texture(&self, width: u32, height: u32, mipmaps: bool) -> PyResult<Texture> {
+        self.create_texture("blank", &image::RgbaImage::new(width, height), mipmaps)
+            .map_err(PyValueError::new_err)
+    }
+
+    pub fn sound_from_bytes(&self, data: &[u8]) -> PyResult<Sound> {
+        Ok(Sound::new(
+            decode_sound(data.to_vec()).map_err(PyRuntimeError::new_err)?,
+        ))
+    }
+
+    /// Creates a sound from raw samples between -1 and 1, given as a list or anything
+    /// exposing a buffer of floats or 16 bit integers, such as a NumPy array. Stereo samples
+    /// are interleaved left then right.
+    #[pyo3(signature = (samples, sample_rate, channels=1))]
+    pub fn sound_from_samples(
+        &self,
+        samples: &Bound<PyAny>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> PyResult<Sound> {
+        let py = samples.py();
+
+        let samples: Vec<f32> = if let Ok(buffer) = PyBuffer::<f32>::get(samples) {
+            buffer.to_vec(py)?
+        } else if let Ok(buffer) = PyBuffer::<f64>::get(samples) {
+            buffer.to_vec(py)?.into_iter().map(|x| x as f32).collect()
+        } else if let Ok(buffer) = PyBuffer::<i16>::get(samples) {
+            buffer
+                .to_vec(py)?
+                .into_iter()
+                .map(|x| x as f32 / -(i16::MIN as f32))
+                .collect()
+        } else {
+            samples.extract()?
+        };
+
+        if sample_rate == 0 {
+            return Err(PyValueError::new_err("Expected sample_rate > 0"));
+        }
+
+        if samples.is_empty() {
+            return Err(PyValueError::new_err("Expected at least one sample"));
+        }
+
+        let frames = match channels {
+            1 => samples.into_iter().map(Frame::from_mono).collect(),
+            2 if samples.len().is_multiple_of(2) => samples
+                .chunks_exact(2)
+                .map(|x| Frame::new(x[0], x[1]))
+                .collect(),
+            2 => return Err(PyValueError::new_err("Stereo samples must come in pairs")),
+            _ => return Err(PyValueError::new_err("Expected 1 or 2 channels")),
+        };
+
+        Ok(Sound::from_frames(sample_rate, frames))
+    }
+
+    /// Starts loading `paths` on worker threads. Images become textures and anything else is
+    /// loaded as a sound.
+    pub fn load_async(&mut self, py: Python, paths: Vec<String>) -> LoadGroup {
+        let mut assets = HashMap::new();
+        let mut pending = Vec::new();
+
+        for path in paths {
+            let key = vfs::normalize(&path);
+
+            let cached = if DecodedAsset::is_image(&path) {
+                self.textures
+                    .get::<Texture>(py, &key)
+                    .map(|x| x.into_any().unbind())
+            } else {
+                self.sounds
+                    .get::<Sound>(py, &key)
+                    .map(|x| x.into_any().unbind())
+            };
+
+            match cached {
+                Some(asset) => {
+                    assets.insert(path, asset);
+                }
+                None if !pending.contains(&path) => pending.push(path),
+                None => {}
+            }
+        }
+
+        let state = Arc::new(Mutex::new(LoadGroupState {
+            total: assets.len() + pending.len(),
+            assets,
+            errors: HashMap::new(),
+        }));
+
+        if !pending.is_empty() {
+            self.pending.push(PendingLoad::spawn(
+                pending,
+                Arc::clone(&self.vfs),
+                Arc::clone(&state),
+            ));
+        }
+
+        LoadGroup { state }
+    }
+
+    /// Forgets any cached asset loaded from `path`, so the next load reads it again.
+    /// The asset itself is freed once nothing references it anymore.
+    pub fn unload(&mut self, path: &str) -> bool {
+        let key = vfs::normalize(path);
+
+        let texture = self.textures.remove(&key);
+        let sound = self.sounds.remove(&key);
+        let music = self.music.remove(&key);
+
+        texture || sound || music
+    }
+
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.sounds.clear();
+        self.music.clear();
+    }
+
+    pub fn atlas(&mut self, paths: Vec<String>, padding: Option<u32>) -> PyResult<Atlas> {
+        let images = paths
+            .into_iter()
+            .map(|path| {
+                let image =
+                    decode_rgba(&path, &self.vfs.read(&path)?).map_err(PyRuntimeError::new_err)?;
+                Ok((path, image))
+            })
+            .collect::<PyResult<_>>()?;
+
+        self.build_atlas(images, padding.unwrap_or(Self::ATLAS_PADDING))
+    }
+
+    /// Packs every readable image under `path`, named by their path relative to it.
+    pub fn atlas_dir(&mut self, path: &str, padding: Option<u32>) -> PyResult<Atlas> {
+        let dir = vfs::normalize(path);
+
+        let images = self
+            .vfs
+            .list(&dir)?
+            .into_iter()
+            .filter(|name| DecodedAsset::is_image(name))
+            .map(|name| {
+                let path = if dir.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{dir}/{name}")
+                };
+                let image =
+                    decode_rgba(&path, &self.vfs.read(&path)?).map_err(PyRuntimeError::new_err)?;
+
+                Ok((name, image))
+            })
+            .collect::<PyResult<_>>()?;
+
+        self.build_atlas(images, padding.unwrap_or(Self::ATLAS_PADDING))
+    }
 }
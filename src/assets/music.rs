@@ -1,22 +1,140 @@
-use std::time::Duration;
+use std::{io::Cursor, path::PathBuf, sync::Arc};
 
 use kira::{
-    sound::static_sound::{StaticSoundData, StaticSoundHandle},
-    Easing, StartTime, Tween,
+    sound::{
+        streaming::{StreamingSoundData, StreamingSoundHandle},
+        FromFileError, PlaybackState, Region,
+    },
+    Easing, Tween,
 };
-use pyo3::prelude::*;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::math::audio::{self, linear_to_db, tween};
 
-use crate::math::audio::linear_to_db;
+/// Where music is streamed from: a file on disk, or the encoded bytes of one read out of an
+/// archive or memory mount.
+#[derive(Debug, Clone)]
+pub enum MusicSource {
+    File(PathBuf),
+    Memory(Arc<[u8]>),
+}
 
-#[pyclass]
+/// Music is decoded a little at a time while it plays rather than all at once when loaded.
+#[pyclass(weakref)]
 #[derive(Debug)]
 pub struct Music {
     pub volume: f32,
     pub speed: f32,
     pub r#loop: bool,
     pub paused: bool,
-    pub data: StaticSoundData,
-    pub handle: Option<StaticSoundHandle>,
+    /// The part of the music that repeats while looping, in seconds. No end means the end of
+    /// the file.
+    pub loop_start: f64,
+    pub loop_end: Option<f64>,
+    pub duration: f64,
+    /// How the next play fades in, set by fading in music that isn't playing.
+    pub fade_in: Option<Tween>,
+    pub source: MusicSource,
+    pub handle: Option<StreamingSoundHandle<FromFileError>>,
+}
+
+impl Music {
+    /// Opens `source` to check it can be played and to read its duration.
+    pub fn new(source: MusicSource) -> Result<Self, String> {
+        let mut music = Self {
+            volume: 1.0,
+            speed: 1.0,
+            r#loop: false,
+            paused: false,
+            loop_start: 0.0,
+            loop_end: None,
+            duration: 0.0,
+            fade_in: None,
+            source,
+            handle: None,
+        };
+        music.duration = music.data()?.duration().as_secs_f64();
+
+        Ok(music)
+    }
+
+    /// Replaces the file the music streams from. Music already playing keeps streaming the
+    /// old file until it is played again.
+    pub fn set_source(&mut self, source: MusicSource) -> Result<(), String> {
+        let previous = std::mem::replace(&mut self.source, source);
+
+        match self.data() {
+            Ok(data) => {
+                self.duration = data.duration().as_secs_f64();
+                Ok(())
+            }
+            Err(e) => {
+                self.source = previous;
+                Err(e)
+            }
+        }
+    }
+
+    pub fn loop_region(&self) -> Option<Region> {
+        self.r#loop.then(|| match self.loop_end {
+            Some(end) => (self.loop_start..end).into(),
+            None => (self.loop_start..).into(),
+        })
+    }
+
+    /// Checks a loop from `start` to `end` seconds, or to the end of the music if `end` is
+    /// `None`, is one the music can play.
+    fn check_loop_region(&self, start: f64, end: Option<f64>) -> PyResult<()> {
+        if !start.is_finite() || start >= self.duration {
+            return Err(PyValueError::new_err(format!(
+                "Expected the loop to start before the end of the music at {} seconds",
+                self.duration
+            )));
+        }
+
+        if end.is_some_and(|end| end.is_nan() || end <= start.max(0.0)) {
+            return Err(PyValueError::new_err(
+                "Expected the loop to end after it starts",
+            ));
+        }
+
+        if end.is_some_and(|end| end > self.duration) {
+            return Err(PyValueError::new_err(format!(
+                "Expected the loop to end by the end of the music at {} seconds",
+                self.duration
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn update_loop_region(&mut self) {
+        let region = self.loop_region();
+
+        if let Some(handle) = self.handle.as_mut() {
+            handle.set_loop_region(region);
+        }
+    }
+
+    /// Opens a new stream of the music with its current settings. Streams can only be played
+    /// once, so each play opens its own.
+    pub fn data(&self) -> Result<StreamingSoundData<FromFileError>, String> {
+        let data = match &self.source {
+            MusicSource::File(path) => StreamingSoundData::from_file(path),
+            MusicSource::Memory(bytes) => {
+                StreamingSoundData::from_cursor(Cursor::new(bytes.clone()))
+            }
+        }
+        .map_err(|e| e.to_string())?;
+
+        Ok(data
+            .volume(linear_to_db(self.volume))
+            .playback_rate(self.speed as f64)
+            .loop_region(self.loop_region()))
+    }
 }
 
 #[pymethods]
@@ -29,17 +147,9 @@ impl Music {
     #[setter]
     pub fn set_volume(&mut self, value: f32) {
         self.volume = value;
-        self.data = self.data.volume(linear_to_db(value));
 
         if let Some(handle) = self.handle.as_mut() {
-            handle.set_volume(
-                linear_to_db(value),
-                Tween {
-                    start_time: StartTime::Immediate,
-                    duration: Duration::from_secs(0),
-                    easing: Easing::Linear,
-                },
-            );
+            handle.set_volume(linear_to_db(value), tween(0.0, Easing::Linear));
         }
     }
 
@@ -51,17 +161,9 @@ impl Music {
     #[setter]
     pub fn set_speed(&mut self, value: f32) {
         self.speed = value;
-        self.data = self.data.playback_rate(value as f64);
 
         if let Some(handle) = self.handle.as_mut() {
-            handle.set_playback_rate(
-                value as f64,
-                Tween {
-                    start_time: StartTime::Immediate,
-                    duration: Duration::from_secs(0),
-                    easing: Easing::Linear,
-                },
-            );
+            handle.set_playback_rate(value as f64, tween(0.0, Easing::Linear));
         }
     }
 
@@ -73,19 +175,51 @@ impl Music {
     #[setter]
     pub fn set_loop(&mut self, value: bool) {
         self.r#loop = value;
-        self.data = if value {
-            self.data.loop_region(..)
-        } else {
-            self.data.loop_region(None)
-        };
+        self.update_loop_region();
+    }
 
-        if let Some(handle) = self.handle.as_mut() {
-            if value {
-                handle.set_loop_region(..);
-            } else {
-                handle.set_loop_region(None);
-            }
-        }
+    #[getter]
+    pub fn get_loop_start(&self) -> f64 {
+        self.loop_start
+    }
+
+    #[setter]
+    pub fn set_loop_start(&mut self, value: f64) -> PyResult<()> {
+        self.check_loop_region(value, self.loop_end)?;
+
+        self.loop_start = value.max(0.0);
+        self.update_loop_region();
+
+        Ok(())
+    }
+
+    /// Where looping jumps back to `loop_start`, or `None` for the end of the music.
+    #[getter]
+    pub fn get_loop_end(&self) -> Option<f64> {
+        self.loop_end
+    }
+
+    #[setter]
+    pub fn set_loop_end(&mut self, value: Option<f64>) -> PyResult<()> {
+        self.check_loop_region(self.loop_start, value)?;
+
+        self.loop_end = value;
+        self.update_loop_region();
+
+        Ok(())
+    }
+
+    /// Loops between `start` and `end` seconds, or the end of the music if `end` is `None`.
+    #[pyo3(signature = (start, end=None))]
+    pub fn set_loop_region(&mut self, start: f64, end: Option<f64>) -> PyResult<()> {
+        self.check_loop_region(start, end)?;
+
+        self.r#loop = true;
+        self.loop_start = start.max(0.0);
+        self.loop_end = end;
+        self.update_loop_region();
+
+        Ok(())
     }
 
     #[getter]
@@ -99,20 +233,92 @@ impl Music {
             self.paused = value;
 
             if value {
-                handle.pause(Tween {
-                    start_time: StartTime::Immediate,
-                    duration: Duration::from_secs(0),
-                    easing: Easing::Linear,
-                });
+                handle.pause(tween(0.0, Easing::Linear));
             } else {
-                handle.resume(Tween {
-                    start_time: StartTime::Immediate,
-                    duration: Duration::from_secs(0),
-                    easing: Easing::Linear,
-                });
+                handle.resume(tween(0.0, Easing::Linear));
             }
         } else {
             self.paused = false;
         }
     }
+
+    /// The length of the music in seconds.
+    #[getter]
+    pub fn get_duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// How far into the music playback is, in seconds.
+    #[getter]
+    pub fn get_position(&self) -> f64 {
+        self.handle.as_ref().map_or(0.0, |x| x.position())
+    }
+
+    /// Whether the music is audible, including while it fades in, out or to a stop.
+    #[getter]
+    pub fn get_is_playing(&self) -> bool {
+        self.handle.as_ref().is_some_and(|x| {
+            matches!(
+                x.state(),
+                PlaybackState::Playing
+                    | PlaybackState::Pausing
+                    | PlaybackState::Resuming
+                    | PlaybackState::Stopping
+            )
+        })
+    }
+
+    /// Stops the music, fading it out over `fade` seconds. It starts from the beginning
+    /// when played again.
+    #[pyo3(signature = (fade=0.0))]
+    pub fn stop(&mut self, fade: f64) {
+        self.paused = false;
+
+        if let Some(handle) = self.handle.as_mut() {
+            handle.stop(tween(fade, Easing::Linear));
+        }
+    }
+
+    /// Jumps to `seconds` into the music. Does nothing until the music has been played.
+    pub fn seek(&mut self, seconds: f64) {
+        if let Some(handle) = self.handle.as_mut() {
+            handle.seek_to(seconds.max(0.0));
+        }
+    }
+
+    /// Resumes paused music, fading its volume in from silence over `seconds`. Music that
+    /// isn't playing fades in like this the next time it is played. Music already playing
+    /// can't fade in, so raises `RuntimeError`; fade it out or stop it first.
+    #[pyo3(signature = (seconds, easing="linear"))]
+    pub fn fade_in(&mut self, seconds: f64, easing: &str) -> PyResult<()> {
+        let tween = tween(seconds, audio::easing(easing)?);
+
+        match self.handle.as_mut() {
+            Some(handle) if handle.state() == PlaybackState::Playing => {
+                return Err(PyRuntimeError::new_err(
+                    "Music is already playing, fade it out or stop it before fading in",
+                ));
+            }
+            Some(handle) if handle.state() != PlaybackState::Stopped => {
+                self.paused = false;
+                handle.resume(tween);
+            }
+            _ => self.fade_in = Some(tween),
+        }
+
+        Ok(())
+    }
+
+    /// Fades the volume out to silence over `seconds`, then pauses the music.
+    #[pyo3(signature = (seconds, easing="linear"))]
+    pub fn fade_out(&mut self, seconds: f64, easing: &str) -> PyResult<()> {
+        let tween = tween(seconds, audio::easing(easing)?);
+
+        if let Some(handle) = self.handle.as_mut() {
+            self.paused = true;
+            handle.pause(tween);
+        }
+
+        Ok(())
+    }
 }
@@ -1,16 +1,75 @@
-use kira::sound::static_sound::StaticSoundData;
-use pyo3::prelude::*;
+use kira::{
+    sound::{
+        static_sound::{StaticSoundData, StaticSoundSettings},
+        PlaybackState,
+    },
+    Easing, Frame,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
 
-use crate::math::audio::linear_to_db;
+use crate::{
+    audio::voice::VoiceHandle,
+    math::audio::{linear_to_db, tween},
+};
 
-#[pyclass]
+#[pyclass(weakref)]
 #[derive(Debug, Clone)]
 pub struct Sound {
     pub volume: f32,
     pub speed: f32,
+    /// How many voices of the sound can play at once, or `None` for no limit.
+    pub max_voices: Option<usize>,
+    /// The voices still playing, oldest first.
+    pub voices: Vec<VoiceHandle>,
     pub data: StaticSoundData,
 }
 
+impl Sound {
+    /// How long a stolen voice takes to fade out, short enough to be unnoticeable but long
+    /// enough not to click.
+    pub const STEAL_FADE: f64 = 0.02;
+
+    pub fn new(data: StaticSoundData) -> Self {
+        Self {
+            volume: 1.0,
+            speed: 1.0,
+            max_voices: None,
+            voices: Vec::new(),
+            data,
+        }
+    }
+
+    /// A sound playing `frames` at `sample_rate` frames per second.
+    pub fn from_frames(sample_rate: u32, frames: Vec<Frame>) -> Self {
+        Self::new(StaticSoundData {
+            sample_rate,
+            frames: frames.into(),
+            settings: StaticSoundSettings::default(),
+            slice: None,
+        })
+    }
+
+    /// Forgets voices that have finished, then stops the oldest ones until another can play
+    /// without going over `max_voices`.
+    pub fn make_room(&mut self) {
+        self.voices
+            .retain(|x| x.lock().unwrap().state() != PlaybackState::Stopped);
+
+        let max = match self.max_voices {
+            Some(max) => max,
+            None => return,
+        };
+
+        while self.voices.len() >= max {
+            self.voices
+                .remove(0)
+                .lock()
+                .unwrap()
+                .stop(tween(Self::STEAL_FADE, Easing::Linear));
+        }
+    }
+}
+
 #[pymethods]
 impl Sound {
     #[getter]
@@ -34,4 +93,32 @@ impl Sound {
         self.speed = value;
         self.data = self.data.playback_rate(value as f64);
     }
+
+    /// The most voices of the sound that play at once. Playing it again past the limit stops
+    /// the oldest voice.
+    #[getter]
+    pub fn get_max_voices(&self) -> Option<usize> {
+        self.max_voices
+    }
+
+    #[setter]
+    pub fn set_max_voices(&mut self, value: Option<usize>) -> PyResult<()> {
+        if value == Some(0) {
+            return Err(PyValueError::new_err(
+                "Expected max_voices to be at least 1",
+            ));
+        }
+
+        self.max_voices = value;
+        Ok(())
+    }
+
+    /// How many voices of the sound are playing.
+    #[getter]
+    pub fn get_voice_count(&self) -> usize {
+        self.voices
+            .iter()
+            .filter(|x| x.lock().unwrap().state() != PlaybackState::Stopped)
+            .count()
+    }
 }
@@ -1,13 +1,236 @@
 use std::sync::Arc;
 
-use pyo3::prelude::*;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
 
-#[pyclass]
+use crate::{assets::bitmap::Bitmap, graphics::mipmaps::MipmapGenerator, math::colors::Color};
+
+#[pyclass(weakref)]
 #[derive(Debug, Clone)]
 pub struct Texture {
     pub path: Arc<String>,
     pub texture: Arc<wgpu::Texture>,
     pub view: Arc<wgpu::TextureView>,
     pub binding: Arc<wgpu::BindGroup>,
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+    /// Set when `texture` has a mip chain to keep up to date.
+    pub mipmaps: Option<Arc<MipmapGenerator>>,
     pub size: Arc<glam::Vec2>,
+    /// The region of `texture` this handle covers, as UV offset (xy) and scale (zw).
+    pub uv: glam::Vec4,
+}
+
+impl Texture {
+    pub const FULL_UV: glam::Vec4 = glam::Vec4::new(0.0, 0.0, 1.0, 1.0);
+
+    pub fn width(&self) -> u32 {
+        self.size.x as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.size.y as u32
+    }
+
+    /// Where this handle's region starts within `texture`, in pixels.
+    pub fn origin(&self) -> (u32, u32) {
+        (
+            (self.uv.x * self.texture.width() as f32).round() as u32,
+            (self.uv.y * self.texture.height() as f32).round() as u32,
+        )
+    }
+
+    /// Textures uploaded in a GPU-compressed format can't be written or read as RGBA.
+    pub fn check_rgba(&self) -> Result<(), String> {
+        if self.texture.format() != MipmapGenerator::FORMAT {
+            return Err(format!(
+                "Texture '{}' is stored as {:?}, not RGBA",
+                self.path,
+                self.texture.format()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Uploads `rgba` over the given rectangle of this handle's region, which must fit inside it.
+    /// Any mip chain is regenerated once before the next draw, however many writes come first.
+    pub fn write(&self, x: u32, y: u32, width: u32, height: u32, rgba: &[u8]) {
+        let (origin_x, origin_y) = self.origin();
+
+        self.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin_x + x,
+                    y: origin_y + y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        if let Some(mipmaps) = &self.mipmaps {
+            mipmaps.mark_dirty(&self.texture);
+        }
+    }
+
+    /// Copies this handle's region back from the GPU, blocking until the copy is done.
+    pub fn read(&self) -> Result<image::RgbaImage, String> {
+        self.check_rgba()?;
+
+        let (width, height) = (self.width(), self.height());
+        let (x, y) = self.origin();
+
+        if width == 0 || height == 0 {
+            return Ok(image::RgbaImage::new(width, height));
+        }
+
+        // Rows in the staging buffer have to be aligned, so they are padded and stripped after.
+        let row_size = 4 * width;
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("Texture readback: {}", self.path)),
+            size: padded_row_size as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Texture readback encoder"),
+            });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        self.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|e| e.to_string())?;
+        receiver
+            .recv()
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+
+        let mut rgba = Vec::with_capacity((row_size * height) as usize);
+        for row in slice.get_mapped_range().chunks(padded_row_size as usize) {
+            rgba.extend_from_slice(&row[..row_size as usize]);
+        }
+        buffer.unmap();
+
+        image::RgbaImage::from_raw(width, height, rgba).ok_or_else(|| "Readback was short".into())
+    }
+}
+
+#[pymethods]
+impl Texture {
+    #[getter]
+    pub fn get_width(&self) -> u32 {
+        self.width()
+    }
+
+    #[getter]
+    pub fn get_height(&self) -> u32 {
+        self.height()
+    }
+
+    /// Overwrites the rectangle `(x, y, width, height)` with raw RGBA pixels, row by row.
+    pub fn set_pixels(&self, rect: (u32, u32, u32, u32), rgba: &[u8]) -> PyResult<()> {
+        let (x, y, width, height) = rect;
+
+        self.check_rgba().map_err(PyRuntimeError::new_err)?;
+
+        if x.saturating_add(width) > self.width() || y.saturating_add(height) > self.height() {
+            return Err(PyValueError::new_err(format!(
+                "Rectangle {rect:?} is outside of the {}x{} texture",
+                self.width(),
+                self.height()
+            )));
+        }
+
+        if rgba.len() != 4 * width as usize * height as usize {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} bytes for a {width}x{height} rectangle",
+                4 * width as usize * height as usize
+            )));
+        }
+
+        if width > 0 && height > 0 {
+            self.write(x, y, width, height, rgba);
+        }
+
+        Ok(())
+    }
+
+    /// Sets a single pixel. Prefer `set_pixels` when changing many at once.
+    pub fn set_pixel(&self, x: u32, y: u32, color: Color) -> PyResult<()> {
+        self.set_pixels((x, y, 1, 1), &color.to_rgba8())
+    }
+
+    /// The texture's pixels as raw RGBA bytes, row by row. This waits for the GPU, so avoid
+    /// calling it every frame.
+    pub fn read_pixels<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let rgba = self.read().map_err(PyRuntimeError::new_err)?;
+        Ok(PyBytes::new(py, &rgba))
+    }
+
+    pub fn to_image(&self) -> PyResult<Bitmap> {
+        let rgba = self.read().map_err(PyRuntimeError::new_err)?;
+        Ok(Bitmap { rgba })
+    }
+
+    pub fn fill(&self, color: Color) -> PyResult<()> {
+        self.check_rgba().map_err(PyRuntimeError::new_err)?;
+
+        let rgba = color
+            .to_rgba8()
+            .repeat(self.width() as usize * self.height() as usize);
+        self.write(0, 0, self.width(), self.height(), &rgba);
+
+        Ok(())
+    }
 }
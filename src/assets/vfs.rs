@@ -0,0 +1,329 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    io::{self, BufReader, ErrorKind, Read},
+    path::{Path, PathBuf},
+    sync::{Mutex, RwLock},
+};
+
+pub enum MountSource {
+    Directory(PathBuf),
+    Zip(Mutex<zip::ZipArchive<BufReader<File>>>),
+    Memory(HashMap<String, Vec<u8>>),
+}
+
+impl MountSource {
+    /// The contents of `path`, or `None` if this source doesn't have it. Errors only when the
+    /// file is there but can't be read.
+    pub fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        match self {
+            Self::Directory(root) => match std::fs::read(root.join(path)) {
+                Ok(data) => Ok(Some(data)),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            },
+            Self::Zip(archive) => {
+                let mut archive = archive.lock().unwrap();
+                let mut file = match archive.by_name(path) {
+                    Ok(file) => file,
+                    Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                    Err(e) => return Err(io::Error::other(e)),
+                };
+
+                let mut data = Vec::with_capacity(file.size() as usize);
+                file.read_to_end(&mut data)?;
+                Ok(Some(data))
+            }
+            Self::Memory(files) => Ok(files.get(path).cloned()),
+        }
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        match self {
+            Self::Directory(root) => root.join(path).is_file(),
+            Self::Zip(archive) => archive.lock().unwrap().index_for_name(path).is_some(),
+            Self::Memory(files) => files.contains_key(path),
+        }
+    }
+
+    /// Every file in this source, relative to its root. A directory that doesn't exist has no
+    /// files.
+    pub fn files(&self) -> io::Result<Vec<String>> {
+        Ok(match self {
+            Self::Directory(root) => {
+                let mut files = Vec::new();
+                let mut pending = vec![root.clone()];
+
+                while let Some(dir) = pending.pop() {
+                    let entries = match std::fs::read_dir(&dir) {
+                        Ok(entries) => entries,
+                        Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                        Err(e) => return Err(e),
+                    };
+
+                    for entry in entries {
+                        let path = entry?.path();
+
+                        if path.is_dir() {
+                            pending.push(path);
+                        } else if let Ok(relative) = path.strip_prefix(root) {
+                            files.push(normalize(&relative.to_string_lossy()));
+                        }
+                    }
+                }
+
+                files
+            }
+            Self::Zip(archive) => archive
+                .lock()
+                .unwrap()
+                .file_names()
+                .filter(|x| !x.ends_with('/'))
+                .map(normalize)
+                .collect(),
+            Self::Memory(files) => files.keys().cloned().collect(),
+        })
+    }
+}
+
+pub struct Mount {
+    pub name: String,
+    /// The virtual directory this source appears under.
+    pub point: String,
+    pub priority: i32,
+    pub source: MountSource,
+}
+
+impl Mount {
+    /// The path of `path` within this mount, if it lies under the mount point.
+    pub fn inner<'a>(&self, path: &'a str) -> Option<&'a str> {
+        if self.point.is_empty() {
+            Some(path)
+        } else {
+            path.strip_prefix(&self.point)?.strip_prefix('/')
+        }
+    }
+
+    pub fn outer(&self, path: &str) -> String {
+        if self.point.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.point, path)
+        }
+    }
+}
+
+/// Mounted asset sources, searched from highest to lowest priority, with the most recently
+/// mounted source winning ties. The root directory is always searched last.
+pub struct Vfs {
+    pub root: RwLock<PathBuf>,
+    pub mounts: RwLock<Vec<Mount>>,
+}
+
+impl Vfs {
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: RwLock::new(PathBuf::from(root)),
+            mounts: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn set_root(&self, root: &str) {
+        *self.root.write().unwrap() = PathBuf::from(root);
+    }
+
+    pub fn mount(&self, mount: Mount) {
+        let mut mounts = self.mounts.write().unwrap();
+
+        mounts.retain(|x| x.name != mount.name);
+        let index = mounts
+            .iter()
+            .position(|x| x.priority <= mount.priority)
+            .unwrap_or(mounts.len());
+        mounts.insert(index, mount);
+    }
+
+    pub fn unmount(&self, name: &str) -> bool {
+        let mut mounts = self.mounts.write().unwrap();
+
+        let count = mounts.len();
+        mounts.retain(|x| x.name != name);
+        mounts.len() != count
+    }
+
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+
+        for mount in self.mounts.read().unwrap().iter() {
+            if let Some(inner) = mount.inner(&path) {
+                if let Some(data) = mount.source.read(inner)? {
+                    return Ok(data);
+                }
+            }
+        }
+
+        let root = MountSource::Directory(self.root.read().unwrap().clone());
+        root.read(&path)?
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, format!("Asset '{path}' not found")))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        let path = normalize(path);
+
+        self.mounts
+            .read()
+            .unwrap()
+            .iter()
+            .any(|mount| mount.inner(&path).is_some_and(|x| mount.source.exists(x)))
+            || self.root.read().unwrap().join(&path).is_file()
+    }
+
+    /// The file on disk `path` is read from, if it comes from a directory.
+    pub fn real_path(&self, path: &str) -> Option<PathBuf> {
+        let path = normalize(path);
+
+        for mount in self.mounts.read().unwrap().iter() {
+            let inner = match mount.inner(&path) {
+                Some(inner) if mount.source.exists(inner) => inner,
+                _ => continue,
+            };
+
+            return match &mount.source {
+                MountSource::Directory(root) => Some(root.join(inner)),
+                _ => None,
+            };
+        }
+
+        let path = self.root.read().unwrap().join(&path);
+        path.is_file().then_some(path)
+    }
+
+    /// Every file visible under the virtual directory `dir`, relative to it.
+    pub fn list(&self, dir: &str) -> io::Result<Vec<String>> {
+        let dir = normalize(dir);
+        let prefix = if dir.is_empty() {
+            dir
+        } else {
+            format!("{dir}/")
+        };
+
+        let root = MountSource::Directory(self.root.read().unwrap().clone());
+        let mounts = self.mounts.read().unwrap();
+
+        let mut files = BTreeSet::new();
+
+        for mount in mounts.iter() {
+            files.extend(mount.source.files()?.iter().map(|x| mount.outer(x)));
+        }
+
+        files.extend(root.files()?);
+
+        Ok(files
+            .into_iter()
+            .filter_map(|x| x.strip_prefix(&prefix).map(str::to_string))
+            .collect())
+    }
+}
+
+/// Turns `path` into the form used for lookups: forward slashes, no leading or `.` segments,
+/// and `..` segments resolved. Going above the top stays at the top, so no path can reach
+/// outside a mount.
+pub fn normalize(path: &str) -> String {
+    let mut segments = Vec::new();
+
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+pub fn open_zip(path: &Path) -> Result<zip::ZipArchive<BufReader<File>>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{normalize, Mount, MountSource, Vfs};
+
+    fn memory(name: &str, point: &str, priority: i32, files: &[(&str, &str)]) -> Mount {
+        Mount {
+            name: name.to_string(),
+            point: point.to_string(),
+            priority,
+            source: MountSource::Memory(HashMap::from_iter(
+                files
+                    .iter()
+                    .map(|(path, data)| (path.to_string(), data.as_bytes().to_vec())),
+            )),
+        }
+    }
+
+    #[test]
+    fn normalize_cleans_separators_and_dots() {
+        assert_eq!(normalize("/sprites//./player.png"), "sprites/player.png");
+        assert_eq!(normalize("sprites\\player.png"), "sprites/player.png");
+        assert_eq!(
+            normalize("sprites/enemies/../player.png"),
+            "sprites/player.png"
+        );
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn normalize_never_leaves_the_top() {
+        assert_eq!(normalize("../secret.txt"), "secret.txt");
+        assert_eq!(normalize("sprites/../../../etc/passwd"), "etc/passwd");
+        assert_eq!(normalize("a/b/../../.."), "");
+    }
+
+    #[test]
+    fn higher_priority_mounts_win() {
+        let vfs = Vfs::new("/nonexistent");
+        vfs.mount(memory("high", "", 1, &[("a.txt", "high")]));
+        vfs.mount(memory("low", "", 0, &[("a.txt", "low"), ("b.txt", "low")]));
+
+        assert_eq!(vfs.read("a.txt").unwrap(), b"high");
+        assert_eq!(vfs.read("b.txt").unwrap(), b"low");
+
+        assert!(vfs.unmount("high"));
+        assert_eq!(vfs.read("a.txt").unwrap(), b"low");
+        assert!(!vfs.unmount("high"));
+    }
+
+    #[test]
+    fn later_mounts_win_ties() {
+        let vfs = Vfs::new("/nonexistent");
+        vfs.mount(memory("first", "", 0, &[("a.txt", "first")]));
+        vfs.mount(memory("second", "", 0, &[("a.txt", "second")]));
+
+        assert_eq!(vfs.read("a.txt").unwrap(), b"second");
+
+        // Remounting under the same name replaces the old mount.
+        vfs.mount(memory("first", "", 0, &[("a.txt", "again")]));
+        assert_eq!(vfs.read("a.txt").unwrap(), b"again");
+    }
+
+    #[test]
+    fn mounts_only_see_paths_under_their_point() {
+        let vfs = Vfs::new("/nonexistent");
+        vfs.mount(memory("sounds", "sounds", 0, &[("jump.wav", "jump")]));
+
+        assert_eq!(vfs.read("sounds/jump.wav").unwrap(), b"jump");
+        assert_eq!(vfs.read("./sounds/extra/../jump.wav").unwrap(), b"jump");
+        let error = vfs.read("jump.wav").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(error.to_string().contains("'jump.wav'"));
+        assert!(!vfs.exists("soundsjump.wav"));
+        assert_eq!(vfs.list("sounds").unwrap(), vec!["jump.wav"]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
+
+pub struct WatchedFile {
+    pub modified: SystemTime,
+    /// A newer modification time seen on the last check, not yet known to be settled.
+    pub pending: Option<SystemTime>,
+}
+
+/// Polls the modification times of loaded asset files.
+pub struct Watcher {
+    pub files: HashMap<String, WatchedFile>,
+    pub last_check: Instant,
+    /// Why the latest reload of each file failed, if it did.
+    pub errors: HashMap<String, String>,
+}
+
+impl Watcher {
+    pub const INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn new() -> Self {
+        Self {
+            files: HashMap::new(),
+            last_check: Instant::now(),
+            errors: HashMap::new(),
+        }
+    }
+
+    /// Whether enough time has passed since the last check to check again.
+    pub fn due(&mut self) -> bool {
+        if self.last_check.elapsed() < Self::INTERVAL {
+            return false;
+        }
+
+        self.last_check = Instant::now();
+        true
+    }
+
+    /// Whether `filepath` should be reloaded. A change is only reported once the modification
+    /// time stays the same across two checks, so files still being written are left alone.
+    pub fn changed(&mut self, filepath: &str) -> bool {
+        let modified = match std::fs::metadata(filepath).and_then(|x| x.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return false,
+        };
+
+        let file = match self.files.get_mut(filepath) {
+            Some(file) => file,
+            None => {
+                self.files.insert(
+                    filepath.to_string(),
+                    WatchedFile {
+                        modified,
+                        pending: None,
+                    },
+                );
+                return false;
+            }
+        };
+
+        if modified == file.modified {
+            file.pending = None;
+            return false;
+        }
+
+        if file.pending != Some(modified) {
+            file.pending = Some(modified);
+            return false;
+        }
+
+        file.modified = modified;
+        file.pending = None;
+        true
+    }
+}
@@ -0,0 +1,386 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use kira::{
+    effect::{Effect, EffectBuilder},
+    info::Info,
+    Frame,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rtrb::{Consumer, Producer, RingBuffer};
+
+/// The samples a track's analyser has received from the audio thread, mixed down to mono.
+pub struct AnalyserState {
+    pub consumer: Mutex<Consumer<f32>>,
+    /// The last [`Analyser::MAX_SIZE`] samples, oldest first.
+    pub history: Mutex<VecDeque<f32>>,
+    pub sample_rate: AtomicU32,
+    /// Whether anything is measuring the track, without which the audio thread sends nothing.
+    pub active: AtomicBool,
+    /// The number of live `Analyser`s reading the track.
+    pub readers: AtomicUsize,
+}
+
+impl AnalyserState {
+    /// Collects the samples sent since the last frame while an `Analyser` is reading them,
+    /// and otherwise has the audio thread stop sending them.
+    pub fn update(&self) {
+        if self.readers.load(Ordering::Relaxed) > 0 {
+            self.drain();
+        } else {
+            self.stop();
+        }
+    }
+
+    /// Has the audio thread start sending samples, if it isn't already.
+    pub fn start(&self) {
+        if self.active.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        // Anything left from the last time the track was measured is out of date.
+        let mut consumer = self.consumer.lock().unwrap();
+        let slots = consumer.slots();
+        consumer
+            .read_chunk(slots)
+            .expect("Failed to read analysed audio")
+            .commit_all();
+        self.history.lock().unwrap().clear();
+    }
+
+    pub fn stop(&self) {
+        self.active.store(false, Ordering::Relaxed);
+    }
+
+    /// Moves everything the audio thread has sent since the last call into the history.
+    pub fn drain(&self) {
+        let mut consumer = self.consumer.lock().unwrap();
+        let mut history = self.history.lock().unwrap();
+
+        let slots = consumer.slots();
+        let chunk = consumer
+            .read_chunk(slots)
+            .expect("Failed to read analysed audio");
+        let (a, b) = chunk.as_slices();
+        history.extend(a.iter().chain(b));
+        chunk.commit_all();
+
+        let excess = history.len().saturating_sub(Analyser::MAX_SIZE);
+        history.drain(..excess);
+    }
+}
+
+/// Copies the audio passing through a track into a ring buffer without changing it.
+pub struct AnalyserEffect {
+    pub producer: Producer<f32>,
+    pub state: Arc<AnalyserState>,
+}
+
+impl Effect for AnalyserEffect {
+    fn init(&mut self, sample_rate: u32, _internal_buffer_size: usize) {
+        self.state.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn on_change_sample_rate(&mut self, sample_rate: u32) {
+        self.state.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn process(&mut self, input: &mut [Frame], _dt: f64, _info: &Info) {
+        if !self.state.active.load(Ordering::Relaxed) {
+            return;
+        }
+
+        for frame in input.iter() {
+            // Once the buffer is full the rest are dropped, until the game catches up.
+            if self.producer.push(frame.as_mono().left).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+pub struct AnalyserBuilder;
+
+impl EffectBuilder for AnalyserBuilder {
+    type Handle = Arc<AnalyserState>;
+
+    fn build(self) -> (Box<dyn Effect>, Self::Handle) {
+        // Room for several frames of audio, in case the game stalls for a moment.
+        let (producer, consumer) = RingBuffer::new(Analyser::MAX_SIZE * 4);
+
+        let state = Arc::new(AnalyserState {
+            consumer: Mutex::new(consumer),
+            history: Mutex::new(VecDeque::with_capacity(Analyser::MAX_SIZE)),
+            sample_rate: AtomicU32::new(0),
+            active: AtomicBool::new(false),
+            readers: AtomicUsize::new(0),
+        });
+
+        let effect = AnalyserEffect {
+            producer,
+            state: Arc::clone(&state),
+        };
+
+        (Box::new(effect), state)
+    }
+}
+
+/// An in-place radix-2 fast Fourier transform. Both slices must be the same power of two
+/// in length.
+pub fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+
+    // Put the samples in bit reversed order.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -std::f32::consts::TAU / len as f32;
+
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+
+                let re_b = re[b] * cos - im[b] * sin;
+                let im_b = re[b] * sin + im[b] * cos;
+
+                re[b] = re[a] - re_b;
+                im[b] = im[a] - im_b;
+                re[a] += re_b;
+                im[a] += im_b;
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+/// Averages `magnitudes` into `bands` bands spaced evenly in pitch. Their edges grow
+/// geometrically from the first bin to the last, skipping the constant offset at bin 0.
+pub fn bands(magnitudes: &[f32], bands: usize) -> Vec<f32> {
+    let count = magnitudes.len() as f32;
+    let edge = |band: usize| count.powf(band as f32 / bands as f32);
+
+    (0..bands)
+        .map(|band| {
+            let start = (edge(band) as usize).clamp(1, magnitudes.len() - 1);
+            let end = (edge(band + 1) as usize).clamp(start + 1, magnitudes.len());
+            let values = &magnitudes[start..end];
+
+            values.iter().sum::<f32>() / values.len() as f32
+        })
+        .collect()
+}
+
+/// Measures the audio passing through a mixer track, updated every frame: its loudness and
+/// how much of each frequency it holds, for visualizers and effects that react to music.
+#[pyclass]
+pub struct Analyser {
+    pub state: Arc<AnalyserState>,
+    pub size: usize,
+}
+
+impl Analyser {
+    pub const MIN_SIZE: usize = 32;
+    pub const MAX_SIZE: usize = 16384;
+
+    /// Starts reading the track `state` belongs to, over the last `size` samples.
+    pub fn new(state: Arc<AnalyserState>, size: usize) -> Self {
+        state.readers.fetch_add(1, Ordering::Relaxed);
+        state.start();
+
+        Self { state, size }
+    }
+
+    pub fn check_size(size: usize) -> PyResult<()> {
+        if !size.is_power_of_two() || !(Self::MIN_SIZE..=Self::MAX_SIZE).contains(&size) {
+            return Err(PyValueError::new_err(format!(
+                "Expected size to be a power of two from {} to {}",
+                Self::MIN_SIZE,
+                Self::MAX_SIZE
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The last `size` samples, padded with silence at the start until that many arrive.
+    fn samples(&self) -> Vec<f32> {
+        let history = self.state.history.lock().unwrap();
+        let start = history.len().saturating_sub(self.size);
+
+        let mut samples = vec![0.0; self.size - (history.len() - start)];
+        samples.extend(history.range(start..));
+        samples
+    }
+}
+
+impl Drop for Analyser {
+    fn drop(&mut self) {
+        self.state.readers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[pymethods]
+impl Analyser {
+    /// The number of samples analysed.
+    #[getter]
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    #[getter]
+    pub fn get_sample_rate(&self) -> u32 {
+        self.state.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// The root mean square of the samples, a measure of loudness from 0 to about 1.
+    #[getter]
+    pub fn get_level(&self) -> f32 {
+        let samples = self.samples();
+        (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// The loudest sample, from 0 to about 1.
+    #[getter]
+    pub fn get_peak(&self) -> f32 {
+        self.samples().iter().fold(0.0, |peak, x| peak.max(x.abs()))
+    }
+
+    /// The strength of each frequency in the samples, from 0 to about 1. Without `bands`
+    /// there are `size / 2` values, the one at `i` for the frequency `i * sample_rate / size`.
+    /// With `bands`, they are averaged into that many bands spaced evenly in pitch, as
+    /// visualizers usually show them.
+    #[pyo3(signature = (bands=None))]
+    pub fn spectrum(&self, bands: Option<usize>) -> PyResult<Vec<f32>> {
+        let size = self.size;
+
+        // A Hann window keeps the edges of the samples from smearing across frequencies.
+        let mut re: Vec<f32> = self
+            .samples()
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let window = 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / size as f32).cos();
+                x * window
+            })
+            .collect();
+        let mut im = vec![0.0; size];
+        fft(&mut re, &mut im);
+
+        // Scaled so a full volume sine wave peaks at 1, allowing for the window halving it.
+        let magnitudes: Vec<f32> = (0..size / 2)
+            .map(|i| (re[i] * re[i] + im[i] * im[i]).sqrt() * 4.0 / size as f32)
+            .collect();
+
+        match bands {
+            Some(0) => Err(PyValueError::new_err("Expected bands >= 1")),
+            Some(count) => Ok(self::bands(&magnitudes, count)),
+            None => Ok(magnitudes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::Ordering, Arc};
+
+    use kira::effect::EffectBuilder;
+
+    use super::{bands, fft, Analyser, AnalyserBuilder};
+
+    fn magnitudes(samples: &[f32]) -> Vec<f32> {
+        let mut re = samples.to_vec();
+        let mut im = vec![0.0; samples.len()];
+        fft(&mut re, &mut im);
+
+        re.iter().zip(&im).map(|(re, im)| re.hypot(*im)).collect()
+    }
+
+    #[test]
+    fn fft_of_an_impulse_is_flat() {
+        let mut samples = vec![0.0; 16];
+        samples[0] = 1.0;
+
+        for magnitude in magnitudes(&samples) {
+            assert!((magnitude - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn fft_finds_a_sine_in_its_bin() {
+        let size = 64;
+        let samples: Vec<f32> = (0..size)
+            .map(|i| (std::f32::consts::TAU * 5.0 * i as f32 / size as f32).sin())
+            .collect();
+
+        for (bin, magnitude) in magnitudes(&samples).into_iter().enumerate() {
+            // A real sine shows up at its frequency and mirrored at the top, half each.
+            let expected = if bin == 5 || bin == size - 5 {
+                size as f32 / 2.0
+            } else {
+                0.0
+            };
+            assert!(
+                (magnitude - expected).abs() < 1e-3,
+                "bin {bin}: {magnitude}"
+            );
+        }
+    }
+
+    #[test]
+    fn bands_grow_geometrically_and_skip_bin_zero() {
+        let magnitudes: Vec<f32> = (0..16).map(|x| x as f32).collect();
+
+        assert_eq!(bands(&magnitudes, 4), vec![1.0, 2.5, 5.5, 11.5]);
+    }
+
+    #[test]
+    fn bands_cover_at_least_one_bin_each() {
+        let magnitudes = [9.0, 1.0, 2.0, 3.0];
+        let bands = bands(&magnitudes, 8);
+
+        assert_eq!(bands.len(), 8);
+        assert!(bands.iter().all(|x| (1.0..=3.0).contains(x)));
+        assert_eq!(bands.last(), Some(&3.0));
+    }
+
+    #[test]
+    fn tracks_stop_sending_once_every_analyser_is_dropped() {
+        // The effect keeps its own reference to the state, as it does on the audio thread.
+        let (_effect, state) = AnalyserBuilder.build();
+        assert!(!state.active.load(Ordering::Relaxed));
+
+        let first = Analyser::new(Arc::clone(&state), 1024);
+        let second = Analyser::new(Arc::clone(&state), 256);
+        state.update();
+        assert!(state.active.load(Ordering::Relaxed));
+
+        drop(first);
+        state.update();
+        assert!(state.active.load(Ordering::Relaxed));
+
+        drop(second);
+        state.update();
+        assert!(!state.active.load(Ordering::Relaxed));
+    }
+}
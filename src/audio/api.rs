@@ -1,10 +1,32 @@
 use pyo3::prelude::*;
 
-use crate::audio::Audio;
+use crate::audio::{
+    analyser::Analyser,
+    clock::{Beat, Clock},
+    effects::Effect,
+    mixer::Track,
+    pool::SoundPool,
+    spatial::Emitter,
+    synth::{Generator, Signal, Synth},
+    voice::Voice,
+    Audio,
+};
 
 pub fn install(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    parent.add_class::<Analyser>()?;
     parent.add_class::<Audio>()?;
+    parent.add_class::<Beat>()?;
+    parent.add_class::<Clock>()?;
+    parent.add_class::<Effect>()?;
+    parent.add_class::<Emitter>()?;
+    parent.add_class::<Generator>()?;
+    parent.add_class::<Signal>()?;
+    parent.add_class::<SoundPool>()?;
+    parent.add_class::<Synth>()?;
+    parent.add_class::<Track>()?;
+    parent.add_class::<Voice>()?;
     parent.add("audio", None::<Audio>)?;
+    parent.add("synth", Synth)?;
 
     Ok(())
 }
@@ -0,0 +1,136 @@
+use std::sync::{Arc, Mutex};
+
+use kira::{
+    clock::{ClockHandle, ClockSpeed, ClockTime},
+    Easing,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::math::audio::tween;
+
+/// A point in time on a clock, for starting audio exactly on a beat.
+#[pyclass(name = "ClockTime")]
+#[derive(Debug, Clone)]
+pub struct Beat {
+    pub time: ClockTime,
+    /// Keeps the clock running while audio waits for this time, as dropping the last handle
+    /// removes it.
+    pub clock: Arc<Mutex<ClockHandle>>,
+}
+
+impl Beat {
+    /// Whether the clock hasn't yet moved past this time.
+    pub fn pending(&self) -> bool {
+        self.clock.lock().unwrap().time().ticks <= self.time.ticks
+    }
+}
+
+#[pymethods]
+impl Beat {
+    #[getter]
+    pub fn get_beat(&self) -> u64 {
+        self.time.ticks
+    }
+}
+
+/// A metronome running on the audio thread, ticking once per beat. Unlike adding up frame
+/// times, it stays in step with the audio it schedules.
+#[pyclass]
+pub struct Clock {
+    pub bpm: f64,
+    pub handle: Arc<Mutex<ClockHandle>>,
+}
+
+impl Clock {
+    pub fn speed(bpm: f64) -> PyResult<ClockSpeed> {
+        if bpm <= 0.0 || !bpm.is_finite() {
+            return Err(PyValueError::new_err("Expected bpm > 0"));
+        }
+
+        Ok(ClockSpeed::TicksPerMinute(bpm))
+    }
+}
+
+#[pymethods]
+impl Clock {
+    #[getter]
+    pub fn get_bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    #[setter]
+    pub fn set_bpm(&mut self, value: f64) -> PyResult<()> {
+        self.change_bpm(value, 0.0)
+    }
+
+    /// Eases the tempo to `bpm` over `duration` seconds.
+    #[pyo3(signature = (bpm, duration=0.0))]
+    pub fn change_bpm(&mut self, bpm: f64, duration: f64) -> PyResult<()> {
+        let speed = Self::speed(bpm)?;
+
+        self.bpm = bpm;
+        self.handle
+            .lock()
+            .unwrap()
+            .set_speed(speed, tween(duration, Easing::Linear));
+
+        Ok(())
+    }
+
+    /// The number of beats that have passed since the clock started.
+    #[getter]
+    pub fn get_current_beat(&self) -> u64 {
+        self.handle.lock().unwrap().time().ticks
+    }
+
+    /// How far the clock is through the current beat, from 0 to 1.
+    #[getter]
+    pub fn get_fraction(&self) -> f64 {
+        self.handle.lock().unwrap().time().fraction
+    }
+
+    /// The beats passed including how far through the current one the clock is.
+    #[getter]
+    pub fn get_position(&self) -> f64 {
+        let time = self.handle.lock().unwrap().time();
+        time.ticks as f64 + time.fraction
+    }
+
+    #[getter]
+    pub fn get_ticking(&self) -> bool {
+        self.handle.lock().unwrap().ticking()
+    }
+
+    /// The time at `beat` beats since the clock started.
+    pub fn beat(&self, beat: u64) -> Beat {
+        Beat {
+            time: ClockTime::from_ticks_u64(&*self.handle.lock().unwrap(), beat),
+            clock: Arc::clone(&self.handle),
+        }
+    }
+
+    /// The next beat that is a multiple of `every`, such as the start of the next bar.
+    #[pyo3(signature = (every=1))]
+    pub fn next_beat(&self, every: u64) -> PyResult<Beat> {
+        if every == 0 {
+            return Err(PyValueError::new_err("Expected every >= 1"));
+        }
+
+        let ticks = self.handle.lock().unwrap().time().ticks;
+        Ok(self.beat((ticks / every + 1) * every))
+    }
+
+    /// Starts the clock, or resumes it after a pause.
+    pub fn start(&self) {
+        self.handle.lock().unwrap().start();
+    }
+
+    pub fn pause(&self) {
+        self.handle.lock().unwrap().pause();
+    }
+
+    /// Stops the clock and resets it to beat 0.
+    pub fn stop(&self) {
+        self.handle.lock().unwrap().stop();
+    }
+}
@@ -0,0 +1,144 @@
+use kira::{
+    command::{command_writer_and_reader, CommandReader, CommandWriter, ValueChangeCommand},
+    effect::{Effect, EffectBuilder},
+    info::Info,
+    Decibels, Frame, Mix, Parameter, Tween, Value,
+};
+
+/// Echoes audio after a delay that can change while it plays, up to [`Delay::MAX_TIME`]
+/// seconds. Kira's own delay fixes its time when it is built, which tracks built before an
+/// effect is chosen can't know.
+pub struct Delay {
+    time: Parameter,
+    feedback: Parameter<Decibels>,
+    mix: Parameter<Mix>,
+    readers: DelayReaders,
+    sample_rate: u32,
+    /// Audio written over the last [`Self::MAX_TIME`] seconds, `position` being the oldest.
+    buffer: Vec<Frame>,
+    position: usize,
+}
+
+struct DelayReaders {
+    time: CommandReader<ValueChangeCommand<f64>>,
+    feedback: CommandReader<ValueChangeCommand<Decibels>>,
+    mix: CommandReader<ValueChangeCommand<Mix>>,
+}
+
+impl Delay {
+    pub const MAX_TIME: f64 = 4.0;
+
+    fn resize(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.buffer = vec![Frame::ZERO; (Self::MAX_TIME * sample_rate as f64) as usize + 1];
+        self.position = 0;
+    }
+}
+
+impl Effect for Delay {
+    fn init(&mut self, sample_rate: u32, _internal_buffer_size: usize) {
+        self.resize(sample_rate);
+    }
+
+    fn on_change_sample_rate(&mut self, sample_rate: u32) {
+        self.resize(sample_rate);
+    }
+
+    fn on_start_processing(&mut self) {
+        self.time.read_command(&mut self.readers.time);
+        self.feedback.read_command(&mut self.readers.feedback);
+        self.mix.read_command(&mut self.readers.mix);
+    }
+
+    fn process(&mut self, input: &mut [Frame], dt: f64, info: &Info) {
+        let elapsed = dt * input.len() as f64;
+        self.time.update(elapsed, info);
+        self.feedback.update(elapsed, info);
+        self.mix.update(elapsed, info);
+
+        let len = self.buffer.len();
+        let frames = input.len() as f64;
+
+        for (i, frame) in input.iter_mut().enumerate() {
+            let amount = (i + 1) as f64 / frames;
+            let delay = (self.time.interpolated_value(amount) * self.sample_rate as f64) as usize;
+            let feedback = self.feedback.interpolated_value(amount).as_amplitude();
+            let mix = self.mix.interpolated_value(amount).0.clamp(0.0, 1.0);
+
+            let echo = self.buffer[(self.position + len - delay.clamp(1, len - 1)) % len];
+            self.buffer[self.position] = *frame + echo * feedback;
+            self.position = (self.position + 1) % len;
+
+            *frame = echo * mix.sqrt() + *frame * (1.0 - mix).sqrt();
+        }
+    }
+}
+
+pub struct DelayBuilder {
+    /// In seconds, clamped to [`Delay::MAX_TIME`].
+    pub time: f64,
+    pub feedback: Decibels,
+    pub mix: Mix,
+}
+
+impl EffectBuilder for DelayBuilder {
+    type Handle = DelayHandle;
+
+    fn build(self) -> (Box<dyn Effect>, Self::Handle) {
+        let (time_writer, time) = command_writer_and_reader();
+        let (feedback_writer, feedback) = command_writer_and_reader();
+        let (mix_writer, mix) = command_writer_and_reader();
+
+        let effect = Delay {
+            time: Parameter::new(Value::Fixed(self.time.min(Delay::MAX_TIME)), 0.5),
+            feedback: Parameter::new(Value::Fixed(self.feedback), Decibels(-6.0)),
+            mix: Parameter::new(Value::Fixed(self.mix), Mix(0.5)),
+            readers: DelayReaders {
+                time,
+                feedback,
+                mix,
+            },
+            sample_rate: 0,
+            buffer: Vec::new(),
+            position: 0,
+        };
+
+        let handle = DelayHandle {
+            time: time_writer,
+            feedback: feedback_writer,
+            mix: mix_writer,
+        };
+
+        (Box::new(effect), handle)
+    }
+}
+
+/// Controls a [`Delay`] from the main thread.
+pub struct DelayHandle {
+    time: CommandWriter<ValueChangeCommand<f64>>,
+    feedback: CommandWriter<ValueChangeCommand<Decibels>>,
+    mix: CommandWriter<ValueChangeCommand<Mix>>,
+}
+
+impl DelayHandle {
+    pub fn set_time(&mut self, seconds: f64, tween: Tween) {
+        self.time.write(ValueChangeCommand {
+            target: Value::Fixed(seconds.min(Delay::MAX_TIME)),
+            tween,
+        });
+    }
+
+    pub fn set_feedback(&mut self, feedback: Decibels, tween: Tween) {
+        self.feedback.write(ValueChangeCommand {
+            target: Value::Fixed(feedback),
+            tween,
+        });
+    }
+
+    pub fn set_mix(&mut self, mix: Mix, tween: Tween) {
+        self.mix.write(ValueChangeCommand {
+            target: Value::Fixed(mix),
+            tween,
+        });
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use kira::{
+    effect::{
+        compressor::{CompressorBuilder, CompressorHandle},
+        distortion::{DistortionBuilder, DistortionHandle, DistortionKind},
+        eq_filter::{EqFilterBuilder, EqFilterHandle, EqFilterKind},
+        filter::{FilterBuilder, FilterHandle, FilterMode},
+        reverb::{ReverbBuilder, ReverbHandle},
+    },
+    track::TrackBuilder,
+    Decibels, Easing, Mix, Tween,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    audio::delay::{Delay, DelayBuilder, DelayHandle},
+    math::audio::tween,
+};
+
+pub enum EffectHandle {
+    Filter(FilterHandle),
+    Reverb(ReverbHandle),
+    Delay(DelayHandle),
+    Distortion(DistortionHandle),
+    Eq(EqFilterHandle),
+    Compressor(CompressorHandle),
+}
+
+impl EffectHandle {
+    /// The parameters given in seconds, which can't be negative.
+    const DURATIONS: &[&str] = &["delay_time", "attack_duration", "release_duration"];
+
+    /// The parameters each kind of effect accepts with their starting values, in the order
+    /// they are listed in errors.
+    pub fn params(kind: &str) -> Option<&'static [(&'static str, f64)]> {
+        Some(match kind {
+            "lowpass" | "highpass" | "bandpass" | "notch" => {
+                &[("cutoff", 1000.0), ("resonance", 0.0), ("mix", 1.0)]
+            }
+            "reverb" => &[
+                ("feedback", 0.9),
+                ("damping", 0.1),
+                ("stereo_width", 1.0),
+                ("mix", 0.5),
+            ],
+            "delay" => &[("delay_time", 0.5), ("feedback", -6.0), ("mix", 0.5)],
+            "distortion" | "soft_distortion" => &[("drive", 0.0), ("mix", 1.0)],
+            "bell" | "low_shelf" | "high_shelf" => {
+                &[("frequency", 1000.0), ("gain", 0.0), ("q", 1.0)]
+            }
+            "compressor" => &[
+                ("threshold", 0.0),
+                ("ratio", 1.0),
+                ("attack_duration", 0.01),
+                ("release_duration", 0.1),
+                ("makeup_gain", 0.0),
+                ("mix", 1.0),
+            ],
+            _ => return None,
+        })
+    }
+
+    /// The slot of a track's [`EffectRack`] effects of `kind` go in. Kinds that only differ by
+    /// a mode share one.
+    pub fn slot(kind: &str) -> &str {
+        match kind {
+            "lowpass" | "highpass" | "bandpass" | "notch" => "filter",
+            "distortion" | "soft_distortion" => "distortion",
+            kind => kind,
+        }
+    }
+
+    /// Switches the effect to the mode of `kind`, for slots shared by several kinds.
+    pub fn configure(&mut self, kind: &str) {
+        match self {
+            Self::Filter(x) => x.set_mode(match kind {
+                "lowpass" => FilterMode::LowPass,
+                "highpass" => FilterMode::HighPass,
+                "bandpass" => FilterMode::BandPass,
+                _ => FilterMode::Notch,
+            }),
+            Self::Distortion(x) => x.set_kind(match kind {
+                "distortion" => DistortionKind::HardClip,
+                _ => DistortionKind::SoftClip,
+            }),
+            _ => {}
+        }
+    }
+
+    /// Checks `value` is one `param` can take.
+    pub fn check(param: &str, value: f64) -> PyResult<()> {
+        if !value.is_finite() {
+            return Err(PyValueError::new_err(format!(
+                "Expected {param} to be a finite number"
+            )));
+        }
+
+        if Self::DURATIONS.contains(&param) && value < 0.0 {
+            return Err(PyValueError::new_err(format!("Expected {param} >= 0")));
+        }
+
+        if param == "delay_time" && value > Delay::MAX_TIME {
+            return Err(PyValueError::new_err(format!(
+                "Expected delay_time <= {}",
+                Delay::MAX_TIME
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Moves `param` to `value` over `tween`, once `value` has passed [`Self::check`]. Returns
+    /// false if the effect has no such parameter.
+    pub fn set(&mut self, param: &str, value: f64, tween: Tween) -> bool {
+        let mix = Mix(value as f32);
+        let decibels = Decibels(value as f32);
+
+        match (self, param) {
+            (Self::Filter(x), "cutoff") => x.set_cutoff(value, tween),
+            (Self::Filter(x), "resonance") => x.set_resonance(value, tween),
+            (Self::Filter(x), "mix") => x.set_mix(mix, tween),
+            (Self::Reverb(x), "feedback") => x.set_feedback(value, tween),
+            (Self::Reverb(x), "damping") => x.set_damping(value, tween),
+            (Self::Reverb(x), "stereo_width") => x.set_stereo_width(value, tween),
+            (Self::Reverb(x), "mix") => x.set_mix(mix, tween),
+            (Self::Delay(x), "delay_time") => x.set_time(value, tween),
+            (Self::Delay(x), "feedback") => x.set_feedback(decibels, tween),
+            (Self::Delay(x), "mix") => x.set_mix(mix, tween),
+            (Self::Distortion(x), "drive") => x.set_drive(decibels, tween),
+            (Self::Distortion(x), "mix") => x.set_mix(mix, tween),
+            (Self::Eq(x), "frequency") => x.set_frequency(value, tween),
+            (Self::Eq(x), "gain") => x.set_gain(decibels, tween),
+            (Self::Eq(x), "q") => x.set_q(value, tween),
+            (Self::Compressor(x), "threshold") => x.set_threshold(value, tween),
+            (Self::Compressor(x), "ratio") => x.set_ratio(value, tween),
+            (Self::Compressor(x), "attack_duration") => {
+                x.set_attack_duration(Duration::from_secs_f64(value), tween)
+            }
+            (Self::Compressor(x), "release_duration") => {
+                x.set_release_duration(Duration::from_secs_f64(value), tween)
+            }
+            (Self::Compressor(x), "makeup_gain") => x.set_makeup_gain(decibels, tween),
+            (Self::Compressor(x), "mix") => x.set_mix(mix, tween),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+pub struct EffectSlot {
+    pub handle: Arc<Mutex<EffectHandle>>,
+    /// Whether `Track.add_effect` has set the effect up, leaving it dry until then.
+    pub used: bool,
+}
+
+/// One effect of each kind, built into every track when it is created. Kira can't add
+/// effects to a track afterwards, so having them all from the start lets an effect added
+/// later reach audio already playing on the track. They run in the order they are listed in.
+pub struct EffectRack {
+    pub slots: HashMap<&'static str, EffectSlot>,
+}
+
+impl EffectRack {
+    pub fn build(builder: &mut TrackBuilder) -> Self {
+        let eq = |builder: &mut TrackBuilder, kind| {
+            EffectHandle::Eq(builder.add_effect(EqFilterBuilder::new(
+                kind,
+                1000.0,
+                Decibels(0.0),
+                1.0,
+            )))
+        };
+
+        let slots = [
+            ("low_shelf", eq(builder, EqFilterKind::LowShelf)),
+            ("bell", eq(builder, EqFilterKind::Bell)),
+            ("high_shelf", eq(builder, EqFilterKind::HighShelf)),
+            (
+                "filter",
+                EffectHandle::Filter(builder.add_effect(FilterBuilder::new().mix(Mix::DRY))),
+            ),
+            (
+                "distortion",
+                EffectHandle::Distortion(
+                    builder.add_effect(DistortionBuilder::new().mix(Mix::DRY)),
+                ),
+            ),
+            (
+                "compressor",
+                EffectHandle::Compressor(
+                    builder.add_effect(CompressorBuilder::new().mix(Mix::DRY)),
+                ),
+            ),
+            (
+                "delay",
+                EffectHandle::Delay(builder.add_effect(DelayBuilder {
+                    time: 0.5,
+                    feedback: Decibels(-6.0),
+                    mix: Mix::DRY,
+                })),
+            ),
+            (
+                "reverb",
+                EffectHandle::Reverb(builder.add_effect(ReverbBuilder::new().mix(Mix::DRY))),
+            ),
+        ];
+
+        Self {
+            slots: slots
+                .into_iter()
+                .map(|(name, handle)| {
+                    let slot = EffectSlot {
+                        handle: Arc::new(Mutex::new(handle)),
+                        used: false,
+                    };
+                    (name, slot)
+                })
+                .collect(),
+        }
+    }
+
+    /// Sets up the effect of `kind` with the given parameters, the rest at their starting
+    /// values, and returns its handle.
+    pub fn add(
+        &mut self,
+        kind: &str,
+        params: &HashMap<String, f64>,
+    ) -> PyResult<Arc<Mutex<EffectHandle>>> {
+        let known = EffectHandle::params(kind)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown effect '{kind}'")))?;
+
+        if let Some(name) = params
+            .keys()
+            .find(|x| !known.iter().any(|(name, _)| name == x))
+        {
+            let names: Vec<&str> = known.iter().map(|(name, _)| *name).collect();
+            return Err(PyValueError::new_err(format!(
+                "Effect '{kind}' has no parameter '{name}', expected one of {names:?}"
+            )));
+        }
+
+        for (name, value) in params {
+            EffectHandle::check(name, *value)?;
+        }
+
+        let name = EffectHandle::slot(kind);
+        let slot = self.slots.get_mut(name).unwrap();
+
+        if slot.used {
+            return Err(PyValueError::new_err(format!(
+                "The track already has a {name} effect, change it with Effect.set instead"
+            )));
+        }
+
+        let mut handle = slot.handle.lock().unwrap();
+        handle.configure(kind);
+
+        for (param, default) in known {
+            let value = params.get(*param).copied().unwrap_or(*default);
+            handle.set(param, value, tween(0.0, Easing::Linear));
+        }
+
+        slot.used = true;
+        Ok(Arc::clone(&slot.handle))
+    }
+}
+
+/// An effect on a mixer track. Volumes (delay feedback, drive, gain, threshold and makeup
+/// gain) are in decibels, frequencies in hertz and durations in seconds.
+#[pyclass]
+pub struct Effect {
+    pub kind: String,
+    pub handle: Arc<Mutex<EffectHandle>>,
+}
+
+#[pymethods]
+impl Effect {
+    #[getter]
+    pub fn get_kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Changes `param` to `value`, easing into it over `duration` seconds.
+    #[pyo3(signature = (param, value, duration=0.0))]
+    pub fn set(&self, param: &str, value: f64, duration: f64) -> PyResult<()> {
+        EffectHandle::check(param, value)?;
+
+        let tween = tween(duration, Easing::Linear);
+
+        if !self.handle.lock().unwrap().set(param, value, tween) {
+            return Err(PyValueError::new_err(format!(
+                "Effect '{}' has no parameter '{param}' that can be changed",
+                self.kind
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use kira::{
+    track::{TrackBuilder, TrackHandle},
+    Easing,
+};
+use pyo3::{prelude::*, types::PyDict};
+
+use crate::{
+    audio::{
+        analyser::{AnalyserBuilder, AnalyserState},
+        effects::{Effect, EffectRack},
+    },
+    math::audio::{linear_to_db, tween},
+};
+
+pub struct MixerTrack {
+    pub handle: TrackHandle,
+    pub effects: EffectRack,
+    /// Measures everything passing through the track, once an `Analyser` is made for it.
+    pub analysis: Arc<AnalyserState>,
+    pub parent: Option<String>,
+    pub volume: f32,
+    pub muted: bool,
+    pub solo: bool,
+}
+
+impl MixerTrack {
+    /// The builder for a track, which carries its effects and analyser from the start so they
+    /// reach audio already playing when they are set up.
+    pub fn builder() -> (TrackBuilder, EffectRack, Arc<AnalyserState>) {
+        let mut builder = TrackBuilder::new();
+        let effects = EffectRack::build(&mut builder);
+        let analysis = builder.add_effect(AnalyserBuilder);
+        (builder, effects, analysis)
+    }
+
+    pub fn new(
+        handle: TrackHandle,
+        effects: EffectRack,
+        analysis: Arc<AnalyserState>,
+        parent: Option<String>,
+    ) -> Self {
+        Self {
+            handle,
+            effects,
+            analysis,
+            parent,
+            volume: 1.0,
+            muted: false,
+            solo: false,
+        }
+    }
+}
+
+/// Every named track sounds can be played on, nested under `"master"`.
+pub struct Mixer {
+    pub tracks: HashMap<String, MixerTrack>,
+}
+
+impl Mixer {
+    pub const MASTER: &str = "master";
+
+    /// Whether `name` is `ancestor` or nested anywhere under it.
+    pub fn is_within(&self, name: &str, ancestor: &str) -> bool {
+        let mut current = Some(name);
+
+        while let Some(name) = current {
+            if name == ancestor {
+                return true;
+            }
+
+            current = self.tracks.get(name).and_then(|x| x.parent.as_deref());
+        }
+
+        false
+    }
+
+    /// Whether soloing lets `name` be heard: while any track is soloed, only soloed tracks,
+    /// the tracks they are nested in and the tracks nested in them are.
+    pub fn is_audible(&self, name: &str) -> bool {
+        let soloed: Vec<&str> = self
+            .tracks
+            .iter()
+            .filter(|(_, track)| track.solo)
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        soloed.is_empty()
+            || soloed
+                .iter()
+                .any(|solo| self.is_within(name, solo) || self.is_within(solo, name))
+    }
+
+    /// Sends the volume of every track to the audio thread, taking mutes and solos into account.
+    pub fn apply(&mut self) {
+        self.apply_over(0.0);
+    }
+
+    /// Like [`Self::apply`], easing into the new volumes over `seconds`.
+    pub fn apply_over(&mut self, seconds: f64) {
+        let volumes: Vec<(String, f32)> = self
+            .tracks
+            .iter()
+            .map(|(name, track)| {
+                let audible = !track.muted && self.is_audible(name);
+                (name.clone(), if audible { track.volume } else { 0.0 })
+            })
+            .collect();
+
+        for (name, volume) in volumes {
+            self.tracks
+                .get_mut(&name)
+                .unwrap()
+                .handle
+                .set_volume(linear_to_db(volume), tween(seconds, Easing::Linear));
+        }
+    }
+}
+
+/// A handle to one of the mixer's tracks.
+#[pyclass]
+pub struct Track {
+    pub name: String,
+    pub mixer: Arc<Mutex<Mixer>>,
+}
+
+impl Track {
+    fn update(&self, change: impl FnOnce(&mut MixerTrack)) {
+        let mut mixer = self.mixer.lock().unwrap();
+
+        if let Some(track) = mixer.tracks.get_mut(&self.name) {
+            change(track);
+        }
+
+        mixer.apply();
+    }
+
+    fn read<T>(&self, value: impl FnOnce(&MixerTrack) -> T) -> T {
+        value(&self.mixer.lock().unwrap().tracks[&self.name])
+    }
+}
+
+#[pymethods]
+impl Track {
+    #[getter]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    #[getter]
+    pub fn get_volume(&self) -> f32 {
+        self.read(|x| x.volume)
+    }
+
+    #[setter]
+    pub fn set_volume(&self, value: f32) {
+        self.update(|x| x.volume = value);
+    }
+
+    /// Eases the volume to `volume` over `duration` seconds, such as to duck music.
+    pub fn fade(&self, volume: f32, duration: f32) {
+        let mut mixer = self.mixer.lock().unwrap();
+
+        if let Some(track) = mixer.tracks.get_mut(&self.name) {
+            track.volume = volume;
+        }
+
+        mixer.apply_over(duration as f64);
+    }
+
+    #[getter]
+    pub fn get_muted(&self) -> bool {
+        self.read(|x| x.muted)
+    }
+
+    #[setter]
+    pub fn set_muted(&self, value: bool) {
+        self.update(|x| x.muted = value);
+    }
+
+    /// While any track is soloed, tracks that aren't soloed (or related to one that is) are
+    /// silenced.
+    #[getter]
+    pub fn get_solo(&self) -> bool {
+        self.read(|x| x.solo)
+    }
+
+    #[setter]
+    pub fn set_solo(&self, value: bool) {
+        self.update(|x| x.solo = value);
+    }
+
+    /// Adds an effect of `kind` with starting parameters given as keyword arguments, applying
+    /// to everything on the track, including audio already playing. A track has one effect of
+    /// each kind, except that the filters (`lowpass`, `highpass`, `bandpass` and `notch`) share
+    /// one, as do the two distortions. Effects run in a fixed order: equalizers, filter,
+    /// distortion, compressor, delay, then reverb.
+    #[pyo3(signature = (kind, **params))]
+    pub fn add_effect(&self, kind: &str, params: Option<&Bound<PyDict>>) -> PyResult<Effect> {
+        let params: HashMap<String, f64> = match params {
+            Some(params) => params.extract()?,
+            None => HashMap::new(),
+        };
+
+        let mut mixer = self.mixer.lock().unwrap();
+        let handle = mixer
+            .tracks
+            .get_mut(&self.name)
+            .unwrap()
+            .effects
+            .add(kind, &params)?;
+
+        Ok(Effect {
+            kind: kind.to_string(),
+            handle,
+        })
+    }
+}
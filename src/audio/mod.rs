@@ -1,17 +1,44 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use kira::{
-    track::{TrackBuilder, TrackHandle},
-    AudioManager, AudioManagerSettings, DefaultBackend, Easing, StartTime, Tween,
+    listener::ListenerHandle, track::SpatialTrackBuilder, AudioManager, AudioManagerSettings,
+    DefaultBackend, Easing, PlaySoundError, StartTime, Tween,
+};
+use pyo3::{
+    exceptions::{PyKeyError, PyRuntimeError, PyValueError},
+    prelude::*,
 };
-use pyo3::prelude::*;
 
 use crate::{
     assets::{music::Music, sound::Sound},
-    math::audio::linear_to_db,
+    audio::{
+        analyser::Analyser,
+        clock::{Beat, Clock},
+        mixer::{Mixer, MixerTrack, Track},
+        pool::SoundPool,
+        spatial::{to_space, Emitter, ListenerTarget, ListenerTargetArg},
+        synth::{Generator, GeneratorData, Signal},
+        voice::Voice,
+    },
+    math::{
+        audio::{linear_to_db, tween},
+        vectors::Vec2,
+    },
 };
 
+pub mod analyser;
 pub mod api;
+pub mod clock;
+pub mod delay;
+pub mod effects;
+pub mod mixer;
+pub mod pool;
+pub mod spatial;
+pub mod synth;
+pub mod voice;
 
 #[derive(FromPyObject)]
 enum PlayableAudio<'a> {
@@ -19,64 +46,398 @@ enum PlayableAudio<'a> {
     Music(Bound<'a, Music>),
     #[pyo3(transparent)]
     Sound(Bound<'a, Sound>),
+    #[pyo3(transparent)]
+    Pool(Bound<'a, SoundPool>),
+    #[pyo3(transparent)]
+    Signal(Bound<'a, Signal>),
+}
+
+impl<'a> PlayableAudio<'a> {
+    fn py(&self) -> Python<'a> {
+        match self {
+            Self::Music(x) => x.py(),
+            Self::Sound(x) => x.py(),
+            Self::Pool(x) => x.py(),
+            Self::Signal(x) => x.py(),
+        }
+    }
 }
 
 #[pyclass]
 pub struct Audio {
     pub volume: f32,
     pub manager: AudioManager<DefaultBackend>,
-    pub tracks: HashMap<String, TrackHandle>,
+    pub mixer: Arc<Mutex<Mixer>>,
+    pub listener: ListenerHandle,
+    pub listener_target: ListenerTarget,
+    /// Times audio is waiting to start at, holding on to their clocks until then.
+    pub scheduled: Vec<Beat>,
 }
 
 impl Audio {
     pub fn new() -> Self {
         let mut manager = AudioManager::new(AudioManagerSettings::default())
             .expect("Failed to create audio manager");
-        let tracks = HashMap::from_iter([(
-            "master".to_string(),
+        let (builder, effects, analysis) = MixerTrack::builder();
+        let master = MixerTrack::new(
             manager
-                .add_sub_track(TrackBuilder::new())
+                .add_sub_track(builder)
                 .expect("Failed to create master audio track"),
-        )]);
+            effects,
+            analysis,
+            None,
+        );
+        let listener = manager
+            .add_listener(glam::Vec3::ZERO, glam::Quat::IDENTITY)
+            .expect("Failed to create audio listener");
 
         Self {
             volume: 1.0,
             manager,
-            tracks,
+            mixer: Arc::new(Mutex::new(Mixer {
+                tracks: HashMap::from_iter([(Mixer::MASTER.to_string(), master)]),
+            })),
+            listener,
+            listener_target: ListenerTarget::Position(glam::Vec2::ZERO),
+            scheduled: Vec::new(),
+        }
+    }
+
+    /// Moves the listener to its target and collects the audio analysers have received,
+    /// called every frame.
+    pub fn update(&mut self, py: Python) {
+        let position = self.listener_target.position(py);
+        self.listener
+            .set_position(to_space(position), Tween::default());
+
+        for track in self.mixer.lock().unwrap().tracks.values() {
+            track.analysis.update();
+        }
+
+        self.scheduled.retain(Beat::pending);
+    }
+
+    pub fn missing_track(name: &str) -> PyErr {
+        PyKeyError::new_err(format!("Audio track '{name}' does not exist"))
+    }
+
+    fn play_error<E: std::fmt::Debug>(track: &str, error: PlaySoundError<E>) -> PyErr {
+        match error {
+            PlaySoundError::SoundLimitReached => {
+                PyRuntimeError::new_err(format!("Too many sounds are playing on track '{track}'"))
+            }
+            PlaySoundError::IntoSoundError(e) => {
+                PyRuntimeError::new_err(format!("Failed to play audio: {e:?}"))
+            }
         }
     }
 }
 
 #[pymethods]
 impl Audio {
+    #[getter]
+    pub fn get_volume(&self) -> f32 {
+        self.volume
+    }
+
     #[setter]
     pub fn set_volume(&mut self, value: f32) {
         self.volume = value;
-        self.tracks.get_mut("master").unwrap().set_volume(
-            linear_to_db(value),
-            Tween {
-                start_time: StartTime::Immediate,
-                duration: Duration::from_secs(0),
-                easing: Easing::Linear,
-            },
-        );
+
+        let mut mixer = self.mixer.lock().unwrap();
+        mixer.tracks.get_mut(Mixer::MASTER).unwrap().volume = value;
+        mixer.apply();
+    }
+
+    /// Where positional sounds are heard from: a `Vec2`, or a `Camera` to follow.
+    #[getter]
+    pub fn get_listener(&self, py: Python) -> PyObject {
+        match &self.listener_target {
+            ListenerTarget::Position(position) => Vec2(*position)
+                .into_pyobject(py)
+                .unwrap()
+                .into_any()
+                .unbind(),
+            ListenerTarget::Camera(camera) => camera.clone_ref(py).into_any(),
+        }
     }
 
-    fn play(&mut self, audio: PlayableAudio) {
+    #[setter]
+    pub fn set_listener(&mut self, py: Python, value: ListenerTargetArg) {
+        self.listener_target = match value {
+            ListenerTargetArg::Position(position) => ListenerTarget::Position(position.0),
+            ListenerTargetArg::Camera(camera) => ListenerTarget::Camera(camera.unbind()),
+        };
+
+        self.update(py);
+    }
+
+    /// Gets the track called `name`, creating it under `parent` (or master) if it doesn't exist.
+    #[pyo3(signature = (name, parent=None))]
+    pub fn track(&mut self, name: &str, parent: Option<&str>) -> PyResult<Track> {
+        let mut mixer = self.mixer.lock().unwrap();
+
+        if !mixer.tracks.contains_key(name) {
+            let parent = parent.unwrap_or(Mixer::MASTER);
+            let (builder, effects, analysis) = MixerTrack::builder();
+            let handle = mixer
+                .tracks
+                .get_mut(parent)
+                .ok_or_else(|| Self::missing_track(parent))?
+                .handle
+                .add_sub_track(builder)
+                .map_err(|_| PyRuntimeError::new_err("Too many audio tracks are in use"))?;
+
+            mixer.tracks.insert(
+                name.to_string(),
+                MixerTrack::new(handle, effects, analysis, Some(parent.to_string())),
+            );
+            mixer.apply();
+        }
+
+        Ok(Track {
+            name: name.to_string(),
+            mixer: Arc::clone(&self.mixer),
+        })
+    }
+
+    pub fn tracks(&self) -> Vec<String> {
+        self.mixer.lock().unwrap().tracks.keys().cloned().collect()
+    }
+
+    /// Measures the audio on the track called `track`, or master if not given, over the last
+    /// `size` samples. It hears everything on the track after its effects, before its volume.
+    #[pyo3(signature = (track=None, size=1024))]
+    pub fn analyser(&self, track: Option<&str>, size: usize) -> PyResult<Analyser> {
+        Analyser::check_size(size)?;
+
+        let track = track.unwrap_or(Mixer::MASTER);
+        let mixer = self.mixer.lock().unwrap();
+        let state = &mixer
+            .tracks
+            .get(track)
+            .ok_or_else(|| Self::missing_track(track))?
+            .analysis;
+
+        Ok(Analyser::new(Arc::clone(state), size))
+    }
+
+    /// Creates a clock ticking once per beat at `bpm`, started unless `start` is false.
+    #[pyo3(signature = (bpm, start=true))]
+    pub fn clock(&mut self, bpm: f64, start: bool) -> PyResult<Clock> {
+        let mut handle = self
+            .manager
+            .add_clock(Clock::speed(bpm)?)
+            .map_err(|_| PyRuntimeError::new_err("Too many audio clocks are in use"))?;
+
+        if start {
+            handle.start();
+        }
+
+        Ok(Clock {
+            bpm,
+            handle: Arc::new(Mutex::new(handle)),
+        })
+    }
+
+    /// Plays `audio` on the track called `track`, or master if not given. Sounds and pools
+    /// return a `Voice` to control what was played with, except for pools cooling down, which
+    /// play nothing. Signals return a `Generator`.
+    ///
+    /// Given a `position`, the audio is placed in the world with an `Emitter` to move it with,
+    /// returned for music and on the voice or generator otherwise. It is at full volume within
+    /// `min_distance` of the listener and silent beyond `max_distance`, fading between the two
+    /// more sharply the higher `rolloff` is.
+    ///
+    /// Given `start_at`, the audio waits to start until that time on its clock. Signals can't
+    /// be scheduled.
+    #[pyo3(signature = (
+        audio,
+        track=None,
+        position=None,
+        min_distance=0.0,
+        max_distance=1000.0,
+        rolloff=1.0,
+        start_at=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn play(
+        &mut self,
+        audio: PlayableAudio,
+        track: Option<&str>,
+        position: Option<Vec2>,
+        min_distance: f32,
+        max_distance: f32,
+        rolloff: f64,
+        start_at: Option<Beat>,
+    ) -> PyResult<Option<PyObject>> {
+        let start_time = match &start_at {
+            Some(_) if matches!(audio, PlayableAudio::Signal(_)) => {
+                return Err(PyValueError::new_err(
+                    "Signals can't be scheduled on a clock",
+                ))
+            }
+            Some(beat) => StartTime::ClockTime(beat.time),
+            None => StartTime::Immediate,
+        };
+
+        if min_distance < 0.0 || max_distance <= min_distance {
+            return Err(PyValueError::new_err(
+                "Expected 0 <= min_distance < max_distance",
+            ));
+        }
+
+        if rolloff <= 0.0 {
+            return Err(PyValueError::new_err("Expected rolloff > 0"));
+        }
+
+        let py = audio.py();
+        let track = track.unwrap_or(Mixer::MASTER);
+        let mut mixer = self.mixer.lock().unwrap();
+        let input = &mut mixer
+            .tracks
+            .get_mut(track)
+            .ok_or_else(|| Self::missing_track(track))?
+            .handle;
+
+        // Pools stand in for one of their sounds, scaling its volume and speed.
+        let (audio, volume, speed) = match audio {
+            PlayableAudio::Pool(pool) => match pool.borrow_mut().choose(py) {
+                Some((sound, volume, speed)) => {
+                    (PlayableAudio::Sound(sound.into_bound(py)), volume, speed)
+                }
+                None => return Ok(None),
+            },
+            audio => (audio, 1.0, 1.0),
+        };
+
+        let mut emitter = match position {
+            Some(position) => {
+                let attenuation = if rolloff == 1.0 {
+                    Easing::Linear
+                } else {
+                    Easing::InPowf(rolloff)
+                };
+
+                let handle = input
+                    .add_spatial_sub_track(
+                        self.listener.id(),
+                        to_space(position.0),
+                        SpatialTrackBuilder::new()
+                            .distances((min_distance, max_distance))
+                            .attenuation_function(attenuation)
+                            .persist_until_sounds_finish(true),
+                    )
+                    .map_err(|_| {
+                        PyRuntimeError::new_err(format!(
+                            "Too many sounds are placed on track '{track}'"
+                        ))
+                    })?;
+
+                Some(Emitter {
+                    position: position.0,
+                    handle: Mutex::new(handle),
+                })
+            }
+            None => None,
+        };
+
+        self.scheduled.extend(start_at);
+
         match audio {
             PlayableAudio::Music(music) => {
-                let handle = self
-                    .manager
-                    .play(music.borrow().data.clone())
-                    .expect("Failed to play music");
+                let mut music = music.borrow_mut();
+                let data = music
+                    .data()
+                    .map_err(PyRuntimeError::new_err)?
+                    .fade_in_tween(music.fade_in.take())
+                    .start_time(start_time);
+                let sound = match emitter.as_mut() {
+                    Some(emitter) => emitter.handle.get_mut().unwrap().play(data),
+                    None => input.play(data),
+                }
+                .map_err(|e| Self::play_error(track, e))?;
+
+                music.paused = false;
+                music.handle = Some(sound);
+
+                emitter
+                    .map(|x| Ok(x.into_pyobject(py)?.into_any().unbind()))
+                    .transpose()
+            }
+            PlayableAudio::Pool(_) => unreachable!(),
+            PlayableAudio::Signal(signal) => {
+                let data = GeneratorData {
+                    node: signal.borrow().node.clone(),
+                    volume: 1.0,
+                };
+                let shared = match emitter.as_mut() {
+                    Some(emitter) => emitter.handle.get_mut().unwrap().play(data),
+                    None => input.play(data),
+                }
+                .map_err(|e| Self::play_error(track, e))?;
+
+                let generator = Generator {
+                    shared,
+                    emitter: emitter.map(|x| Py::new(py, x)).transpose()?,
+                };
 
-                music.borrow_mut().handle = Some(handle);
+                Ok(Some(generator.into_pyobject(py)?.into_any().unbind()))
             }
             PlayableAudio::Sound(sound) => {
-                self.manager
-                    .play(sound.borrow().data.clone())
-                    .expect("Failed to play sound");
+                let mut sound = sound.borrow_mut();
+                sound.make_room();
+
+                let (volume, speed) = (sound.volume * volume, sound.speed * speed);
+                let data = sound
+                    .data
+                    .volume(linear_to_db(volume))
+                    .playback_rate(speed as f64)
+                    .start_time(start_time);
+                let handle = match emitter.as_mut() {
+                    Some(emitter) => emitter.handle.get_mut().unwrap().play(data),
+                    None => input.play(data),
+                }
+                .map_err(|e| Self::play_error(track, e))?;
+                let handle = Arc::new(Mutex::new(handle));
+                sound.voices.push(Arc::clone(&handle));
+
+                let voice = Voice {
+                    handle,
+                    volume,
+                    speed,
+                    panning: 0.0,
+                    emitter: emitter.map(|x| Py::new(py, x)).transpose()?,
+                };
+
+                Ok(Some(voice.into_pyobject(py)?.into_any().unbind()))
             }
         }
     }
+
+    /// Fades `from_music` out to a stop while `to_music` starts playing on `track` and fades
+    /// in, both over `seconds`.
+    #[pyo3(signature = (from_music, to_music, seconds, track=None))]
+    fn crossfade(
+        &mut self,
+        from_music: &Bound<Music>,
+        to_music: Bound<Music>,
+        seconds: f64,
+        track: Option<&str>,
+    ) -> PyResult<()> {
+        from_music.borrow_mut().stop(seconds);
+        to_music.borrow_mut().fade_in = Some(tween(seconds, Easing::Linear));
+
+        self.play(
+            PlayableAudio::Music(to_music),
+            track,
+            None,
+            0.0,
+            1000.0,
+            1.0,
+            None,
+        )?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,191 @@
+use std::time::{Duration, Instant};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand::Rng;
+
+use crate::assets::sound::Sound;
+
+/// A set of interchangeable sounds, such as footsteps. Playing the pool plays one of them at
+/// random, never the same one twice in a row, with its volume and speed scaled by a random
+/// amount within `volume` and `speed`.
+#[pyclass]
+pub struct SoundPool {
+    pub sounds: Vec<Py<Sound>>,
+    pub volume: (f32, f32),
+    pub speed: (f32, f32),
+    /// The shortest time between plays, in seconds. Plays sooner than that are skipped.
+    pub cooldown: f64,
+    pub last: Option<usize>,
+    pub last_played: Option<Instant>,
+}
+
+impl SoundPool {
+    fn check_range(name: &str, (min, max): (f32, f32)) -> PyResult<(f32, f32)> {
+        if !(min >= 0.0 && max >= min && max.is_finite()) {
+            return Err(PyValueError::new_err(format!(
+                "Expected {name} to be a range (min, max) with 0 <= min <= max"
+            )));
+        }
+
+        Ok((min, max))
+    }
+
+    fn check_cooldown(cooldown: f64) -> PyResult<f64> {
+        if !cooldown.is_finite() {
+            return Err(PyValueError::new_err(
+                "Expected cooldown to be a finite number",
+            ));
+        }
+
+        Ok(cooldown.max(0.0))
+    }
+
+    /// Picks the sound to play next with the amounts to scale its volume and speed by, or
+    /// `None` if the pool is cooling down.
+    pub fn choose(&mut self, py: Python) -> Option<(Py<Sound>, f32, f32)> {
+        let now = Instant::now();
+
+        if let Some(last_played) = self.last_played {
+            if now.duration_since(last_played) < Duration::from_secs_f64(self.cooldown) {
+                return None;
+            }
+        }
+
+        let mut rng = rand::thread_rng();
+        let index = pick(self.sounds.len(), self.last, &mut rng);
+
+        self.last = Some(index);
+        self.last_played = Some(now);
+
+        Some((
+            self.sounds[index].clone_ref(py),
+            rng.gen_range(self.volume.0..=self.volume.1),
+            rng.gen_range(self.speed.0..=self.speed.1),
+        ))
+    }
+}
+
+/// A random index below `len`, never `last` unless it is the only one. Skipping over the last
+/// index keeps the rest equally likely.
+fn pick(len: usize, last: Option<usize>, rng: &mut impl Rng) -> usize {
+    match last {
+        Some(last) if len > 1 => {
+            let index = rng.gen_range(0..len - 1);
+            if index >= last {
+                index + 1
+            } else {
+                index
+            }
+        }
+        _ => rng.gen_range(0..len),
+    }
+}
+
+#[pymethods]
+impl SoundPool {
+    #[new]
+    #[pyo3(signature = (sounds, volume=(1.0, 1.0), speed=(1.0, 1.0), cooldown=0.0))]
+    pub fn new(
+        sounds: Vec<Py<Sound>>,
+        volume: (f32, f32),
+        speed: (f32, f32),
+        cooldown: f64,
+    ) -> PyResult<Self> {
+        if sounds.is_empty() {
+            return Err(PyValueError::new_err(
+                "A sound pool needs at least one sound",
+            ));
+        }
+
+        Ok(Self {
+            sounds,
+            volume: Self::check_range("volume", volume)?,
+            speed: Self::check_range("speed", speed)?,
+            cooldown: Self::check_cooldown(cooldown)?,
+            last: None,
+            last_played: None,
+        })
+    }
+
+    #[getter]
+    pub fn get_sounds(&self, py: Python) -> Vec<Py<Sound>> {
+        self.sounds.iter().map(|x| x.clone_ref(py)).collect()
+    }
+
+    #[getter]
+    pub fn get_volume(&self) -> (f32, f32) {
+        self.volume
+    }
+
+    #[setter]
+    pub fn set_volume(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.volume = Self::check_range("volume", value)?;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_speed(&self) -> (f32, f32) {
+        self.speed
+    }
+
+    #[setter]
+    pub fn set_speed(&mut self, value: (f32, f32)) -> PyResult<()> {
+        self.speed = Self::check_range("speed", value)?;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_cooldown(&self) -> f64 {
+        self.cooldown
+    }
+
+    #[setter]
+    pub fn set_cooldown(&mut self, value: f64) -> PyResult<()> {
+        self.cooldown = Self::check_cooldown(value)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::pick;
+
+    #[test]
+    fn single_sound_pools_repeat() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert_eq!(pick(1, None, &mut rng), 0);
+        assert_eq!(pick(1, Some(0), &mut rng), 0);
+    }
+
+    #[test]
+    fn two_sound_pools_alternate() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut last = pick(2, None, &mut rng);
+
+        for _ in 0..100 {
+            let index = pick(2, Some(last), &mut rng);
+            assert_eq!(index, 1 - last);
+            last = index;
+        }
+    }
+
+    #[test]
+    fn picks_never_repeat_and_reach_every_other_sound() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut counts = [0; 5];
+        let mut last = None;
+
+        for _ in 0..1000 {
+            let index = pick(counts.len(), last, &mut rng);
+            assert_ne!(Some(index), last);
+
+            counts[index] += 1;
+            last = Some(index);
+        }
+
+        assert!(counts.iter().all(|x| *x > 0), "{counts:?}");
+    }
+}
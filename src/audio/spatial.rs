@@ -0,0 +1,59 @@
+use std::sync::Mutex;
+
+use kira::{track::SpatialTrackHandle, Tween};
+use pyo3::prelude::*;
+
+use crate::{graphics::camera::Camera, math::vectors::Vec2};
+
+/// What the listener hears from: a fixed point, or wherever a camera is looking.
+pub enum ListenerTarget {
+    Position(glam::Vec2),
+    Camera(Py<Camera>),
+}
+
+#[derive(FromPyObject)]
+pub enum ListenerTargetArg<'a> {
+    #[pyo3(transparent)]
+    Position(Vec2),
+    #[pyo3(transparent)]
+    Camera(Bound<'a, Camera>),
+}
+
+impl ListenerTarget {
+    pub fn position(&self, py: Python) -> glam::Vec2 {
+        match self {
+            Self::Position(position) => *position,
+            Self::Camera(camera) => camera.borrow(py).position.borrow(py).0,
+        }
+    }
+}
+
+/// Maps a point in the world onto the plane the listener and emitters sit on.
+pub fn to_space(position: glam::Vec2) -> glam::Vec3 {
+    position.extend(0.0)
+}
+
+/// A sound playing at a point in the world. Its volume falls off with distance from the
+/// listener and it pans towards the side the listener hears it from.
+#[pyclass]
+pub struct Emitter {
+    pub position: glam::Vec2,
+    pub handle: Mutex<SpatialTrackHandle>,
+}
+
+#[pymethods]
+impl Emitter {
+    #[getter]
+    pub fn get_position(&self) -> Vec2 {
+        Vec2(self.position)
+    }
+
+    #[setter]
+    pub fn set_position(&mut self, value: Vec2) {
+        self.position = value.0;
+        self.handle
+            .lock()
+            .unwrap()
+            .set_position(to_space(value.0), Tween::default());
+    }
+}
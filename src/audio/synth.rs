@@ -0,0 +1,427 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc,
+};
+
+use kira::{
+    info::Info,
+    sound::{Sound as KiraSound, SoundData},
+    Frame,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{assets::sound::Sound, audio::spatial::Emitter};
+
+/// The shape of an oscillator's wave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wave {
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+    /// Random values held for half a cycle each, like the noise channel of old sound chips.
+    Noise,
+}
+
+impl Wave {
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "sine" => Ok(Self::Sine),
+            "square" => Ok(Self::Square),
+            "triangle" => Ok(Self::Triangle),
+            "sawtooth" => Ok(Self::Sawtooth),
+            "noise" => Ok(Self::Noise),
+            name => Err(PyValueError::new_err(format!(
+                "Unknown wave '{name}', expected 'sine', 'square', 'triangle', 'sawtooth' or 'noise'"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Oscillator {
+    pub wave: Wave,
+    pub frequency: f32,
+    pub volume: f32,
+    /// How far through the current cycle the wave is, from 0 to 1.
+    pub phase: f32,
+    pub noise: f32,
+    pub seed: u32,
+}
+
+impl Oscillator {
+    pub fn new(wave: Wave, frequency: f32, volume: f32) -> Self {
+        Self {
+            wave,
+            frequency,
+            volume,
+            phase: 0.0,
+            noise: 0.0,
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    pub fn next(&mut self, dt: f32) -> f32 {
+        let phase = self.phase;
+        let value = match self.wave {
+            Wave::Sine => (phase * std::f32::consts::TAU).sin(),
+            Wave::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Wave::Sawtooth => 2.0 * phase - 1.0,
+            Wave::Noise => self.noise,
+        };
+
+        self.phase += self.frequency.max(0.0) * dt;
+
+        // Noise picks a new value every half cycle.
+        if self.wave == Wave::Noise && (self.phase >= 1.0 || (phase < 0.5 && self.phase >= 0.5)) {
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 17;
+            self.seed ^= self.seed << 5;
+            self.noise = self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+        }
+
+        self.phase = self.phase.fract();
+        value * self.volume
+    }
+}
+
+/// A graph of oscillators, combined by mixing them together or multiplying them.
+#[derive(Debug, Clone)]
+pub enum Node {
+    Oscillator(Oscillator),
+    Mix(Vec<Node>),
+    Product(Box<Node>, Box<Node>),
+    Gain(Box<Node>, f32),
+}
+
+impl Node {
+    /// The next sample of the graph, `dt` seconds after the last.
+    pub fn next(&mut self, dt: f32) -> f32 {
+        match self {
+            Self::Oscillator(oscillator) => oscillator.next(dt),
+            Self::Mix(nodes) => nodes.iter_mut().map(|x| x.next(dt)).sum(),
+            Self::Product(a, b) => a.next(dt) * b.next(dt),
+            Self::Gain(node, gain) => node.next(dt) * *gain,
+        }
+    }
+
+    /// Sounds must have at least one sample, which a rendered sound of no length wouldn't.
+    pub fn check_duration(duration: f32) -> PyResult<()> {
+        if !(duration > 0.0 && duration.is_finite()) {
+            return Err(PyValueError::new_err("Expected a finite duration > 0"));
+        }
+
+        Ok(())
+    }
+
+    /// Renders `duration` seconds of the graph into a sound, shaped by `envelope`. The duration
+    /// must have passed [`Self::check_duration`].
+    pub fn render(
+        &mut self,
+        duration: f32,
+        sample_rate: u32,
+        envelope: Option<Envelope>,
+        mut each: impl FnMut(&mut Self, f32),
+    ) -> Sound {
+        let dt = 1.0 / sample_rate as f32;
+        let count = (duration * sample_rate as f32).ceil() as usize;
+
+        let frames = (0..count)
+            .map(|i| {
+                let t = i as f32 * dt;
+                each(self, t / duration);
+
+                let gain = envelope.map_or(1.0, |x| x.gain(t, duration));
+                Frame::from_mono(self.next(dt) * gain)
+            })
+            .collect();
+
+        Sound::from_frames(sample_rate, frames)
+    }
+}
+
+/// How the volume of a tone rises and falls: the seconds it takes to rise to full volume,
+/// the seconds it then takes to fall to the sustain level, that level, and the seconds it
+/// takes to fall silent at the end.
+#[derive(Debug, Clone, Copy, FromPyObject)]
+pub struct Envelope(pub f32, pub f32, pub f32, pub f32);
+
+impl Envelope {
+    pub fn gain(&self, t: f32, duration: f32) -> f32 {
+        let Self(attack, decay, sustain, release) = *self;
+
+        let level = if t < attack {
+            t / attack
+        } else if t < attack + decay {
+            1.0 - (1.0 - sustain) * (t - attack) / decay
+        } else {
+            sustain
+        };
+
+        let release = if release > 0.0 {
+            ((duration - t) / release).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        level * release
+    }
+}
+
+/// A sound made of oscillators, built with `synth`. Signals can be added to mix them,
+/// multiplied together or by a number, rendered into a `Sound`, or played as they are to
+/// generate their samples while they play.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Signal {
+    pub node: Node,
+}
+
+#[derive(FromPyObject)]
+pub enum SignalOrGain {
+    #[pyo3(transparent)]
+    Signal(Signal),
+    #[pyo3(transparent)]
+    Gain(f32),
+}
+
+#[pymethods]
+impl Signal {
+    pub fn __add__(&self, other: Signal) -> Self {
+        let nodes = match (self.node.clone(), other.node) {
+            (Node::Mix(mut a), Node::Mix(b)) => {
+                a.extend(b);
+                a
+            }
+            (Node::Mix(mut a), b) => {
+                a.push(b);
+                a
+            }
+            (a, b) => vec![a, b],
+        };
+
+        Self {
+            node: Node::Mix(nodes),
+        }
+    }
+
+    pub fn __mul__(&self, other: SignalOrGain) -> Self {
+        let node = Box::new(self.node.clone());
+
+        Self {
+            node: match other {
+                SignalOrGain::Signal(other) => Node::Product(node, Box::new(other.node)),
+                SignalOrGain::Gain(gain) => Node::Gain(node, gain),
+            },
+        }
+    }
+
+    pub fn __rmul__(&self, other: SignalOrGain) -> Self {
+        self.__mul__(other)
+    }
+
+    /// Renders `duration` seconds of the signal into a sound.
+    #[pyo3(signature = (duration, envelope=None, sample_rate=44100))]
+    pub fn render(
+        &self,
+        duration: f32,
+        envelope: Option<Envelope>,
+        sample_rate: u32,
+    ) -> PyResult<Sound> {
+        Node::check_duration(duration)?;
+
+        Ok(self
+            .node
+            .clone()
+            .render(duration, sample_rate.max(1), envelope, |_, _| {}))
+    }
+}
+
+/// Builds sounds from code, for retro sound effects.
+#[pyclass]
+pub struct Synth;
+
+#[pymethods]
+impl Synth {
+    /// A sound `duration` seconds long of a single `wave`, sliding from `frequency` to
+    /// `end_frequency` if given.
+    #[pyo3(signature = (
+        frequency,
+        duration,
+        wave="square",
+        envelope=None,
+        volume=1.0,
+        end_frequency=None,
+        sample_rate=44100,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn tone(
+        &self,
+        frequency: f32,
+        duration: f32,
+        wave: &str,
+        envelope: Option<Envelope>,
+        volume: f32,
+        end_frequency: Option<f32>,
+        sample_rate: u32,
+    ) -> PyResult<Sound> {
+        Node::check_duration(duration)?;
+
+        let end_frequency = end_frequency.unwrap_or(frequency);
+        let mut node = Node::Oscillator(Oscillator::new(Wave::parse(wave)?, frequency, volume));
+
+        Ok(
+            node.render(duration, sample_rate.max(1), envelope, |node, t| {
+                if let Node::Oscillator(oscillator) = node {
+                    oscillator.frequency = frequency + (end_frequency - frequency) * t;
+                }
+            }),
+        )
+    }
+
+    #[pyo3(signature = (wave="sine", frequency=440.0, volume=1.0))]
+    pub fn oscillator(&self, wave: &str, frequency: f32, volume: f32) -> PyResult<Signal> {
+        Ok(Signal {
+            node: Node::Oscillator(Oscillator::new(Wave::parse(wave)?, frequency, volume)),
+        })
+    }
+
+    /// White noise, changing `frequency` times a second.
+    #[pyo3(signature = (volume=1.0, frequency=22050.0))]
+    pub fn noise(&self, volume: f32, frequency: f32) -> Signal {
+        Signal {
+            node: Node::Oscillator(Oscillator::new(Wave::Noise, frequency * 0.5, volume)),
+        }
+    }
+}
+
+/// State shared between a `Generator` and the sound generating on the audio thread.
+#[derive(Debug)]
+pub struct GeneratorShared {
+    /// Each of these holds the bits of an `f32`.
+    pub volume: AtomicU32,
+    pub fade: AtomicU32,
+    pub stopping: AtomicBool,
+    pub finished: AtomicBool,
+}
+
+/// A signal waiting to be played.
+pub struct GeneratorData {
+    pub node: Node,
+    pub volume: f32,
+}
+
+impl SoundData for GeneratorData {
+    type Error = ();
+    type Handle = Arc<GeneratorShared>;
+
+    fn into_sound(self) -> Result<(Box<dyn KiraSound>, Self::Handle), Self::Error> {
+        let shared = Arc::new(GeneratorShared {
+            volume: AtomicU32::new(self.volume.to_bits()),
+            fade: AtomicU32::new(0.0f32.to_bits()),
+            stopping: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        });
+
+        let sound = GeneratorSound {
+            node: self.node,
+            shared: Arc::clone(&shared),
+            gain: 1.0,
+        };
+
+        Ok((Box::new(sound), shared))
+    }
+}
+
+struct GeneratorSound {
+    node: Node,
+    shared: Arc<GeneratorShared>,
+    /// Falls from 1 to 0 as the sound fades out after being stopped.
+    gain: f32,
+}
+
+impl KiraSound for GeneratorSound {
+    fn process(&mut self, out: &mut [Frame], dt: f64, _info: &Info) {
+        let dt = dt as f32;
+        let volume = f32::from_bits(self.shared.volume.load(Ordering::Relaxed));
+        let stopping = self.shared.stopping.load(Ordering::Relaxed);
+        let fade = f32::from_bits(self.shared.fade.load(Ordering::Relaxed));
+
+        for frame in out {
+            if stopping {
+                self.gain = if fade > 0.0 {
+                    (self.gain - dt / fade).max(0.0)
+                } else {
+                    0.0
+                };
+            }
+
+            *frame = Frame::from_mono(self.node.next(dt) * volume * self.gain);
+        }
+
+        if self.gain <= 0.0 {
+            self.shared.finished.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.gain <= 0.0
+    }
+}
+
+/// A signal playing on the audio thread, its samples generated as they are needed. It plays
+/// until stopped, or until the generator is garbage collected.
+#[pyclass]
+pub struct Generator {
+    pub shared: Arc<GeneratorShared>,
+    pub emitter: Option<Py<Emitter>>,
+}
+
+#[pymethods]
+impl Generator {
+    #[getter]
+    pub fn get_volume(&self) -> f32 {
+        f32::from_bits(self.shared.volume.load(Ordering::Relaxed))
+    }
+
+    #[setter]
+    pub fn set_volume(&self, value: f32) {
+        self.shared
+            .volume
+            .store(value.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// The emitter placing the generator in the world, if it was played at a position.
+    #[getter]
+    pub fn get_emitter(&self, py: Python) -> Option<Py<Emitter>> {
+        self.emitter.as_ref().map(|x| x.clone_ref(py))
+    }
+
+    #[getter]
+    pub fn get_is_playing(&self) -> bool {
+        !self.shared.finished.load(Ordering::Relaxed)
+    }
+
+    /// Stops generating, fading out over `fade` seconds.
+    #[pyo3(signature = (fade=0.0))]
+    pub fn stop(&self, fade: f32) {
+        self.shared
+            .fade
+            .store(fade.max(0.0).to_bits(), Ordering::Relaxed);
+        self.shared.stopping.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Generator {
+    fn drop(&mut self) {
+        // Nothing can stop the sound once the generator is gone, so it would play forever.
+        self.shared.stopping.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+
+use kira::{
+    sound::{static_sound::StaticSoundHandle, PlaybackState},
+    Easing, Panning,
+};
+use pyo3::prelude::*;
+
+use crate::{
+    audio::spatial::Emitter,
+    math::audio::{linear_to_db, tween},
+};
+
+/// Shared between a `Voice` and the `Sound` it plays, so the sound can steal it.
+pub type VoiceHandle = Arc<Mutex<StaticSoundHandle>>;
+
+/// One playing instance of a sound, for controlling it after it starts.
+#[pyclass]
+pub struct Voice {
+    pub handle: VoiceHandle,
+    pub volume: f32,
+    pub speed: f32,
+    pub panning: f32,
+    pub emitter: Option<Py<Emitter>>,
+}
+
+#[pymethods]
+impl Voice {
+    #[getter]
+    pub fn get_volume(&self) -> f32 {
+        self.volume
+    }
+
+    #[setter]
+    pub fn set_volume(&mut self, value: f32) {
+        self.volume = value;
+        self.handle
+            .lock()
+            .unwrap()
+            .set_volume(linear_to_db(value), tween(0.0, Easing::Linear));
+    }
+
+    #[getter]
+    pub fn get_speed(&self) -> f32 {
+        self.speed
+    }
+
+    #[setter]
+    pub fn set_speed(&mut self, value: f32) {
+        self.speed = value;
+        self.handle
+            .lock()
+            .unwrap()
+            .set_playback_rate(value as f64, tween(0.0, Easing::Linear));
+    }
+
+    /// Where the voice sits between the speakers, from -1 (left) to 1 (right).
+    #[getter]
+    pub fn get_panning(&self) -> f32 {
+        self.panning
+    }
+
+    #[setter]
+    pub fn set_panning(&mut self, value: f32) {
+        self.panning = value.clamp(-1.0, 1.0);
+        self.handle
+            .lock()
+            .unwrap()
+            .set_panning(Panning(self.panning), tween(0.0, Easing::Linear));
+    }
+
+    /// The emitter placing the voice in the world, if it was played at a position.
+    #[getter]
+    pub fn get_emitter(&self, py: Python) -> Option<Py<Emitter>> {
+        self.emitter.as_ref().map(|x| x.clone_ref(py))
+    }
+
+    #[getter]
+    pub fn get_is_playing(&self) -> bool {
+        !matches!(
+            self.handle.lock().unwrap().state(),
+            PlaybackState::Paused | PlaybackState::WaitingToResume | PlaybackState::Stopped
+        )
+    }
+
+    /// Stops the voice for good, fading it out over `fade` seconds.
+    #[pyo3(signature = (fade=0.0))]
+    pub fn stop(&self, fade: f64) {
+        self.handle
+            .lock()
+            .unwrap()
+            .stop(tween(fade, Easing::Linear));
+    }
+
+    #[pyo3(signature = (fade=0.0))]
+    pub fn pause(&self, fade: f64) {
+        self.handle
+            .lock()
+            .unwrap()
+            .pause(tween(fade, Easing::Linear));
+    }
+
+    #[pyo3(signature = (fade=0.0))]
+    pub fn resume(&self, fade: f64) {
+        self.handle
+            .lock()
+            .unwrap()
+            .resume(tween(fade, Easing::Linear));
+    }
+}
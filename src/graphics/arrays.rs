@@ -0,0 +1,194 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{assets::texture::Texture, graphics::builtin::VideoBuiltins};
+
+/// The first slot of `entries` that is empty, or past the end if it can still grow, in a set
+/// holding up to [`TextureArrays::SIZE`] entries.
+fn free_slot<T>(entries: &[Option<T>]) -> Option<usize> {
+    entries
+        .iter()
+        .position(Option::is_none)
+        .or_else(|| (entries.len() < TextureArrays::SIZE as usize).then_some(entries.len()))
+}
+
+/// Up to [`TextureArrays::SIZE`] textures bound together, so sprites using any of them can
+/// share a single draw call.
+pub struct TextureSet {
+    pub label: String,
+    pub textures: Vec<Option<Texture>>,
+    pub binding: Option<Arc<wgpu::BindGroup>>,
+}
+
+impl TextureSet {
+    pub fn new(index: usize) -> Self {
+        Self {
+            label: format!("Texture set {index}"),
+            textures: Vec::new(),
+            binding: None,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        free_slot(&self.textures).is_none()
+    }
+
+    /// Places `texture` in the first free slot and returns it. The binding must be rebuilt
+    /// afterwards.
+    pub fn push(&mut self, texture: &Texture) -> u32 {
+        let slot = free_slot(&self.textures).expect("Texture set is full");
+
+        if slot == self.textures.len() {
+            self.textures.push(Some(texture.clone()));
+        } else {
+            self.textures[slot] = Some(texture.clone());
+        }
+
+        slot as u32
+    }
+
+    /// Recreates the binding after the set of textures changed. Batches are keyed by binding,
+    /// so those created with the previous one are retired instead of reused.
+    pub fn rebuild(&mut self, device: &wgpu::Device, builtins: &VideoBuiltins) {
+        let layout = builtins
+            .layouts
+            .texture_array
+            .as_ref()
+            .expect("Texture arrays are not supported");
+
+        // Unused slots are filled with any other texture, as every entry must be bound.
+        let filler = match self.textures.iter().flatten().next() {
+            Some(texture) => texture,
+            None => {
+                self.binding = None;
+                return;
+            }
+        };
+
+        let views: Vec<&wgpu::TextureView> = (0..TextureArrays::SIZE as usize)
+            .map(|i| {
+                self.textures
+                    .get(i)
+                    .and_then(Option::as_ref)
+                    .unwrap_or(filler)
+                    .view
+                    .as_ref()
+            })
+            .collect();
+
+        self.binding = Some(Arc::new(device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: Some(&self.label),
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureViewArray(&views),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&builtins.sampler),
+                    },
+                ],
+            },
+        )));
+    }
+}
+
+/// Assigns textures to slots in texture sets. Assignments persist across frames, so each set's
+/// binding only changes when a texture is first seen or released.
+pub struct TextureArrays {
+    pub sets: Vec<TextureSet>,
+    /// Slots keyed by the address of each texture's GPU resource.
+    pub slots: HashMap<usize, (usize, u32)>,
+}
+
+impl TextureArrays {
+    /// Must match the binding array length in `sprite_array.wgsl`.
+    pub const SIZE: u32 = 16;
+
+    pub fn new() -> Self {
+        Self {
+            sets: Vec::new(),
+            slots: HashMap::new(),
+        }
+    }
+
+    /// Returns the set index and slot within that set for `texture`.
+    pub fn slot(
+        &mut self,
+        device: &wgpu::Device,
+        builtins: &VideoBuiltins,
+        texture: &Texture,
+    ) -> (usize, u32) {
+        let key = Arc::as_ptr(&texture.texture) as usize;
+        if let Some(slot) = self.slots.get(&key) {
+            return *slot;
+        }
+
+        let index = match self.sets.iter().position(|set| !set.is_full()) {
+            Some(index) => index,
+            None => {
+                self.sets.push(TextureSet::new(self.sets.len()));
+                self.sets.len() - 1
+            }
+        };
+
+        let set = &mut self.sets[index];
+        let slot = (index, set.push(texture));
+        set.rebuild(device, builtins);
+
+        self.slots.insert(key, slot);
+        slot
+    }
+
+    /// Frees the slots of textures nothing else references anymore, so their GPU memory
+    /// can be released.
+    pub fn collect(&mut self, device: &wgpu::Device, builtins: &VideoBuiltins) {
+        for set in &mut self.sets {
+            let mut changed = false;
+
+            for entry in &mut set.textures {
+                if let Some(texture) = entry {
+                    if Arc::strong_count(&texture.texture) == 1 {
+                        self.slots.remove(&(Arc::as_ptr(&texture.texture) as usize));
+                        *entry = None;
+                        changed = true;
+                    }
+                }
+            }
+
+            if changed {
+                set.rebuild(device, builtins);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{free_slot, TextureArrays};
+
+    #[test]
+    fn free_slot_grows_until_full() {
+        let mut entries = Vec::new();
+
+        for i in 0..TextureArrays::SIZE as usize {
+            assert_eq!(free_slot(&entries), Some(i));
+            entries.push(Some(i));
+        }
+
+        assert_eq!(free_slot(&entries), None);
+    }
+
+    #[test]
+    fn free_slot_reuses_released_slots_first() {
+        let mut entries: Vec<Option<usize>> = (0..TextureArrays::SIZE as usize).map(Some).collect();
+        entries[3] = None;
+        entries[7] = None;
+
+        assert_eq!(free_slot(&entries), Some(3));
+
+        entries.truncate(5);
+        assert_eq!(free_slot(&entries), Some(3));
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use pyo3::{exceptions::PyIndexError, prelude::*};
+
+use crate::{
+    assets::texture::Texture,
+    graphics::{
+        draw::{DrawInstance, RawInstance},
+        stack::VideoStack,
+    },
+    math::{colors::Color, vectors::Vec2},
+};
+
+/// Instances of a [`SpriteBatch`], kept resident on the GPU between frames.
+pub struct SpriteBatchData {
+    pub instances: Vec<RawInstance>,
+    pub textures: Vec<Texture>,
+    /// Consecutive instances sharing a texture, drawn with a single call each.
+    pub runs: Vec<(Arc<wgpu::BindGroup>, Range<u32>)>,
+    pub buffer: Option<wgpu::Buffer>,
+    pub capacity: usize,
+    /// The range of instances modified since the last upload.
+    pub dirty: Option<Range<usize>>,
+}
+
+impl SpriteBatchData {
+    pub fn new() -> Self {
+        Self {
+            instances: Vec::new(),
+            textures: Vec::new(),
+            runs: Vec::new(),
+            buffer: None,
+            capacity: 0,
+            dirty: None,
+        }
+    }
+
+    pub fn mark_dirty(&mut self, range: Range<usize>) {
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(range.start)..dirty.end.max(range.end),
+            None => range,
+        });
+    }
+
+    pub fn push(&mut self, texture: &Texture, instance: RawInstance) -> usize {
+        let index = self.instances.len();
+
+        self.instances.push(instance);
+        self.textures.push(texture.clone());
+        self.mark_dirty(index..index + 1);
+        extend_runs(&mut self.runs, &texture.binding, index as u32);
+
+        index
+    }
+
+    pub fn rebuild_runs(&mut self) {
+        self.runs.clear();
+
+        for (index, texture) in self.textures.iter().enumerate() {
+            extend_runs(&mut self.runs, &texture.binding, index as u32);
+        }
+    }
+
+    /// Uploads the dirty range, growing the buffer (and re-uploading everything) if needed.
+    pub fn upload(&mut self, video: &VideoStack) {
+        if let Some(capacity) = grown_capacity(self.capacity, self.instances.len()) {
+            self.capacity = capacity;
+            self.buffer = Some(video.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Sprite batch buffer"),
+                size: (std::mem::size_of::<RawInstance>() * self.capacity) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+            self.dirty = Some(0..self.instances.len());
+        }
+
+        let (buffer, dirty) = match (self.buffer.as_ref(), self.dirty.take()) {
+            (Some(buffer), Some(dirty)) => (buffer, dirty),
+            _ => return,
+        };
+
+        let end = dirty.end.min(self.instances.len());
+        if dirty.start < end {
+            video.queue.write_buffer(
+                buffer,
+                (std::mem::size_of::<RawInstance>() * dirty.start) as wgpu::BufferAddress,
+                bytemuck::cast_slice(&self.instances[dirty.start..end]),
+            );
+        }
+    }
+}
+
+/// The capacity a buffer holding `capacity` instances needs to grow to for `len`, if it must.
+fn grown_capacity(capacity: usize, len: usize) -> Option<usize> {
+    (len > capacity).then(|| len.next_power_of_two())
+}
+
+/// Adds instance `index` to the last run if it shares its binding, or starts a new run.
+fn extend_runs<T>(runs: &mut Vec<(Arc<T>, Range<u32>)>, binding: &Arc<T>, index: u32) {
+    match runs.last_mut() {
+        Some((last, range)) if Arc::ptr_eq(last, binding) => range.end = index + 1,
+        _ => runs.push((Arc::clone(binding), index..index + 1)),
+    }
+}
+
+#[pyclass]
+pub struct SpriteBatch {
+    pub data: Arc<Mutex<SpriteBatchData>>,
+}
+
+#[pymethods]
+impl SpriteBatch {
+    #[new]
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(SpriteBatchData::new())),
+        }
+    }
+
+    pub fn add(
+        &self,
+        texture: &Texture,
+        position: &Vec2,
+        rotation: Option<f32>,
+        scale: Option<Vec2>,
+        tint: Option<Color>,
+    ) -> usize {
+        let instance = DrawInstance::sprite(texture, position, rotation, scale, tint).into_raw();
+
+        self.data.lock().unwrap().push(texture, instance)
+    }
+
+    pub fn set(
+        &self,
+        index: usize,
+        texture: &Texture,
+        position: &Vec2,
+        rotation: Option<f32>,
+        scale: Option<Vec2>,
+        tint: Option<Color>,
+    ) -> PyResult<()> {
+        let mut data = self.data.lock().unwrap();
+
+        if index >= data.instances.len() {
+            return Err(PyIndexError::new_err("Sprite batch index out of range"));
+        }
+
+        data.instances[index] =
+            DrawInstance::sprite(texture, position, rotation, scale, tint).into_raw();
+        data.mark_dirty(index..index + 1);
+
+        if !Arc::ptr_eq(&data.textures[index].binding, &texture.binding) {
+            data.textures[index] = texture.clone();
+            data.rebuild_runs();
+        }
+
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        let mut data = self.data.lock().unwrap();
+
+        data.instances.clear();
+        data.textures.clear();
+        data.runs.clear();
+        data.dirty = None;
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.data.lock().unwrap().instances.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{extend_runs, grown_capacity, SpriteBatchData};
+
+    #[test]
+    fn dirty_ranges_merge_into_one_span() {
+        let mut data = SpriteBatchData::new();
+
+        data.mark_dirty(4..5);
+        data.mark_dirty(1..2);
+        assert_eq!(data.dirty, Some(1..5));
+
+        data.mark_dirty(2..3);
+        assert_eq!(data.dirty, Some(1..5));
+
+        data.mark_dirty(8..10);
+        assert_eq!(data.dirty, Some(1..10));
+    }
+
+    #[test]
+    fn runs_split_when_the_binding_changes() {
+        let (a, b) = (Arc::new(0), Arc::new(0));
+        let mut runs = Vec::new();
+
+        for (index, binding) in [&a, &a, &b, &a].into_iter().enumerate() {
+            extend_runs(&mut runs, binding, index as u32);
+        }
+
+        let ranges: Vec<_> = runs.iter().map(|(_, range)| range.clone()).collect();
+        assert_eq!(ranges, vec![0..2, 2..3, 3..4]);
+        assert!(Arc::ptr_eq(&runs[0].0, &a));
+        assert!(Arc::ptr_eq(&runs[1].0, &b));
+        assert!(Arc::ptr_eq(&runs[2].0, &a));
+    }
+
+    #[test]
+    fn buffer_grows_to_powers_of_two_only_when_full() {
+        assert_eq!(grown_capacity(0, 0), None);
+        assert_eq!(grown_capacity(0, 1), Some(1));
+        assert_eq!(grown_capacity(4, 4), None);
+        assert_eq!(grown_capacity(4, 5), Some(8));
+        assert_eq!(grown_capacity(8, 3), None);
+        assert_eq!(grown_capacity(8, 100), Some(128));
+    }
+}
@@ -1,14 +1,16 @@
-use std::sync::Arc;
+use std::{num::NonZeroU32, sync::Arc};
 
 use crate::{
     graphics::{
-        draw::RawInstance, pipeline::GraphicsPipeline, stack::VideoStack, target::RenderTarget,
+        arrays::TextureArrays, draw::RawInstance, mipmaps::MipmapGenerator,
+        pipeline::GraphicsPipeline, stack::VideoStack, target::RenderTarget,
     },
     math::vertex::Vertex,
 };
 
 pub struct BuiltinLayouts {
     pub texture: Arc<wgpu::BindGroupLayout>,
+    pub texture_array: Option<Arc<wgpu::BindGroupLayout>>,
     pub camera: Arc<wgpu::BindGroupLayout>,
 }
 
@@ -38,6 +40,32 @@ impl BuiltinLayouts {
                 ],
             });
 
+        let texture_array = video.texture_arrays.then(|| {
+            video
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Texture array bind group layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: NonZeroU32::new(TextureArrays::SIZE),
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                })
+        });
+
         let camera = video
             .device
             .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -56,6 +84,7 @@ impl BuiltinLayouts {
 
         Self {
             texture: Arc::new(texture),
+            texture_array: texture_array.map(Arc::new),
             camera: Arc::new(camera),
         }
     }
@@ -63,74 +92,98 @@ impl BuiltinLayouts {
 
 pub struct BuiltinPipelines {
     pub sprite: Arc<GraphicsPipeline>,
+    /// Samples from a binding array of textures, indexed per instance.
+    pub sprite_array: Option<Arc<GraphicsPipeline>>,
 }
 
 impl BuiltinPipelines {
     pub fn new(video: &VideoStack, layouts: &BuiltinLayouts) -> Self {
-        let sprite = {
-            let shader = video
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some("Sprite shader"),
-                    source: wgpu::ShaderSource::Wgsl(mink_shaders::SPRITE.into()),
-                });
-
-            let layout = video
-                .device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Sprite pipeline layout"),
-                    bind_group_layouts: &[&layouts.texture],
-                    push_constant_ranges: &[],
-                });
-
-            let pipeline = video
-                .device
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Sprite pipeline"),
-                    layout: Some(&layout),
-                    vertex: wgpu::VertexState {
-                        module: &shader,
-                        entry_point: Some("vs_main"),
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                        buffers: &[Vertex::buffer_layout(), RawInstance::buffer_layout()],
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: Some("fs_main"),
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: video.config.format,
-                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                            write_mask: wgpu::ColorWrites::all(),
-                        })],
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: None,
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        unclipped_depth: false,
-                        conservative: false,
-                    },
-                    depth_stencil: None,
-                    multisample: wgpu::MultisampleState {
-                        count: RenderTarget::SAMPLE_COUNT,
-                        mask: !0,
-                        alpha_to_coverage_enabled: false,
-                    },
-                    multiview: None,
-                    cache: None,
-                });
+        let sprite = Self::sprite_pipeline(
+            video,
+            "Sprite",
+            mink_shaders::SPRITE,
+            &[&layouts.texture, &layouts.camera],
+        );
 
-            GraphicsPipeline {
-                layout: Arc::new(layout),
-                pipeline: Arc::new(pipeline),
-            }
-        };
+        let sprite_array = layouts.texture_array.as_ref().map(|texture_array| {
+            Self::sprite_pipeline(
+                video,
+                "Sprite array",
+                mink_shaders::SPRITE_ARRAY,
+                &[texture_array, &layouts.camera],
+            )
+        });
 
         Self {
             sprite: Arc::new(sprite),
+            sprite_array: sprite_array.map(Arc::new),
+        }
+    }
+
+    fn sprite_pipeline(
+        video: &VideoStack,
+        label: &str,
+        source: &str,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> GraphicsPipeline {
+        let shader = video
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(&format!("{label} shader")),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+
+        let layout = video
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(&format!("{label} pipeline layout")),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = video
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&format!("{label} pipeline")),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    buffers: &[Vertex::buffer_layout(), RawInstance::buffer_layout()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: video.config.format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::all(),
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: RenderTarget::SAMPLE_COUNT,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            });
+
+        GraphicsPipeline {
+            layout: Arc::new(layout),
+            pipeline: Arc::new(pipeline),
         }
     }
 }
@@ -139,12 +192,14 @@ pub struct VideoBuiltins {
     pub layouts: BuiltinLayouts,
     pub pipelines: BuiltinPipelines,
     pub sampler: wgpu::Sampler,
+    pub mipmaps: Arc<MipmapGenerator>,
 }
 
 impl VideoBuiltins {
     pub fn new(video: &VideoStack) -> Self {
         let layouts = BuiltinLayouts::new(video);
         let pipelines = BuiltinPipelines::new(video, &layouts);
+        let mipmaps = MipmapGenerator::new(&video.device, &video.queue, &layouts.texture);
 
         let sampler = video.device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -160,6 +215,7 @@ impl VideoBuiltins {
             layouts,
             pipelines,
             sampler,
+            mipmaps: Arc::new(mipmaps),
         }
     }
 }
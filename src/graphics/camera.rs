@@ -1,7 +1,54 @@
+use std::sync::Arc;
+
 use pyo3::prelude::*;
 
 use crate::math::vectors::Vec2;
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RawCamera {
+    pub view_projection: [[f32; 4]; 4],
+}
+
+/// A uniform buffer holding a single view-projection matrix, bound once per batch.
+pub struct CameraBinding {
+    pub buffer: wgpu::Buffer,
+    pub binding: Arc<wgpu::BindGroup>,
+}
+
+impl CameraBinding {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Camera uniform buffer"),
+            size: std::mem::size_of::<RawCamera>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera binding"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            buffer,
+            binding: Arc::new(binding),
+        }
+    }
+
+    pub fn write(&self, queue: &wgpu::Queue, matrix: glam::Mat4) {
+        let raw = RawCamera {
+            view_projection: matrix.to_cols_array_2d(),
+        };
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&raw));
+    }
+}
+
 #[pyclass]
 #[derive(Debug)]
 pub struct Camera {
@@ -43,6 +90,28 @@ impl Camera {
         projection * view
     }
 
+    /// The world-space bounding box (min, max) of everything visible through `matrix`,
+    /// found by unprojecting the corners of clip space. Rotated views yield a looser box.
+    pub fn visible_bounds(matrix: glam::Mat4) -> (glam::Vec2, glam::Vec2) {
+        let inverse = matrix.inverse();
+
+        let corners = [
+            glam::Vec3::new(-1.0, -1.0, 0.0),
+            glam::Vec3::new(1.0, -1.0, 0.0),
+            glam::Vec3::new(1.0, 1.0, 0.0),
+            glam::Vec3::new(-1.0, 1.0, 0.0),
+        ]
+        .map(|corner| inverse.project_point3(corner).truncate());
+
+        corners.iter().fold(
+            (
+                glam::Vec2::splat(f32::INFINITY),
+                glam::Vec2::splat(f32::NEG_INFINITY),
+            ),
+            |(min, max), corner| (min.min(*corner), max.max(*corner)),
+        )
+    }
+
     pub fn matrix<'a>(&self, py: Python<'a>, viewport_size: glam::Vec2) -> glam::Mat4 {
         let size = match &self.size {
             Some(size) => size.borrow(py).0,
@@ -117,3 +186,102 @@ impl Camera {
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use glam::Vec2;
+
+    use super::Camera;
+    use crate::math::matrices::{bounds_overlap, model_bounds, model_matrix};
+
+    fn assert_bounds(actual: (Vec2, Vec2), min: Vec2, max: Vec2) {
+        assert!(
+            actual.0.abs_diff_eq(min, 1e-3) && actual.1.abs_diff_eq(max, 1e-3),
+            "expected {:?}, got {actual:?}",
+            (min, max)
+        );
+    }
+
+    #[test]
+    fn visible_bounds_cover_the_camera_size_around_its_position() {
+        let matrix = Camera::build_matrix(Vec2::new(200.0, 100.0), Vec2::new(10.0, 20.0), 0.0, 1.0);
+
+        assert_bounds(
+            Camera::visible_bounds(matrix),
+            Vec2::new(-90.0, -30.0),
+            Vec2::new(110.0, 70.0),
+        );
+    }
+
+    #[test]
+    fn visible_bounds_shrink_with_zoom() {
+        let matrix = Camera::build_matrix(Vec2::new(200.0, 100.0), Vec2::ZERO, 0.0, 2.0);
+
+        assert_bounds(
+            Camera::visible_bounds(matrix),
+            Vec2::new(-50.0, -25.0),
+            Vec2::new(50.0, 25.0),
+        );
+    }
+
+    #[test]
+    fn visible_bounds_contain_rotated_views() {
+        let size = Vec2::new(200.0, 100.0);
+
+        let quarter = Camera::build_matrix(size, Vec2::ZERO, FRAC_PI_2, 1.0);
+        assert_bounds(
+            Camera::visible_bounds(quarter),
+            Vec2::new(-50.0, -100.0),
+            Vec2::new(50.0, 100.0),
+        );
+
+        // Turned by 45 degrees, the view's corners reach out along both axes equally.
+        let extent = 150.0 / 2.0_f32.sqrt();
+        let eighth = Camera::build_matrix(size, Vec2::ZERO, FRAC_PI_4, 1.0);
+        assert_bounds(
+            Camera::visible_bounds(eighth),
+            Vec2::splat(-extent),
+            Vec2::splat(extent),
+        );
+    }
+
+    #[test]
+    fn model_bounds_contain_rotated_and_scaled_sprites() {
+        let scaled = model_matrix(&Vec2::new(5.0, 5.0), 0.0, &Vec2::new(4.0, 2.0));
+        assert_bounds(
+            model_bounds(&scaled),
+            Vec2::new(3.0, 4.0),
+            Vec2::new(7.0, 6.0),
+        );
+
+        let rotated = model_matrix(&Vec2::ZERO, FRAC_PI_2, &Vec2::new(4.0, 2.0));
+        assert_bounds(
+            model_bounds(&rotated),
+            Vec2::new(-1.0, -2.0),
+            Vec2::new(1.0, 2.0),
+        );
+    }
+
+    #[test]
+    fn sprites_straddling_the_edge_stay_visible() {
+        let view = Camera::visible_bounds(Camera::build_matrix(
+            Vec2::new(200.0, 100.0),
+            Vec2::ZERO,
+            0.0,
+            1.0,
+        ));
+        let size = Vec2::new(20.0, 20.0);
+
+        let straddling = model_matrix(&Vec2::new(105.0, 0.0), 0.0, &size);
+        assert!(bounds_overlap(view, model_bounds(&straddling)));
+
+        let outside = model_matrix(&Vec2::new(115.0, 0.0), 0.0, &size);
+        assert!(!bounds_overlap(view, model_bounds(&outside)));
+
+        // Turned by 45 degrees, the sprite's corner reaches back into view.
+        let rotated = model_matrix(&Vec2::new(112.0, 0.0), FRAC_PI_4, &size);
+        assert!(bounds_overlap(view, model_bounds(&rotated)));
+    }
+}
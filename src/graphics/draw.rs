@@ -1,18 +1,36 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
 
 use pyo3::prelude::*;
 
 use crate::{
     assets::texture::Texture,
-    graphics::{builtin::VideoBuiltins, camera::Camera, quad::Quad, stack::VideoStack},
-    math::{colors::Color, matrices::model_matrix, vectors::Vec2},
+    graphics::{
+        arrays::TextureArrays,
+        batch::{SpriteBatch, SpriteBatchData},
+        builtin::VideoBuiltins,
+        camera::{Camera, CameraBinding},
+        quad::Quad,
+        stack::VideoStack,
+    },
+    math::{
+        colors::Color,
+        matrices::{bounds_overlap, model_bounds, model_matrix},
+        vectors::Vec2,
+    },
 };
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct RawInstance {
-    pub matrix: [[f32; 4]; 4],
+    pub transform: [f32; 4],
+    pub translation: [f32; 2],
     pub color: [f32; 4],
+    pub uv: [f32; 4],
+    pub texture: u32,
 }
 
 impl RawInstance {
@@ -29,22 +47,22 @@ impl RawInstance {
                 wgpu::VertexAttribute {
                     offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
                     shader_location: 3,
-                    format: wgpu::VertexFormat::Float32x4,
+                    format: wgpu::VertexFormat::Float32x2,
                 },
                 wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
                     shader_location: 4,
                     format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
                     shader_location: 5,
                     format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    offset: std::mem::size_of::<[f32; 14]>() as wgpu::BufferAddress,
                     shader_location: 6,
-                    format: wgpu::VertexFormat::Float32x4,
+                    format: wgpu::VertexFormat::Uint32,
                 },
             ],
         }
@@ -53,16 +71,41 @@ impl RawInstance {
 
 #[derive(Debug, Clone)]
 pub struct DrawInstance {
-    pub camera: glam::Mat4,
-    pub model: glam::Mat4,
+    pub model: glam::Affine2,
     pub color: Color,
+    /// UV offset (xy) and scale (zw) of the texture region to sample.
+    pub uv: glam::Vec4,
+    /// Index into the bound texture array, if one is in use.
+    pub texture: u32,
 }
 
 impl DrawInstance {
+    pub fn sprite(
+        texture: &Texture,
+        position: &glam::Vec2,
+        rotation: Option<f32>,
+        scale: Option<Vec2>,
+        tint: Option<Color>,
+    ) -> Self {
+        Self {
+            model: model_matrix(
+                position,
+                rotation.unwrap_or(0.0),
+                &(*texture.size * scale.map(|x| x.into()).unwrap_or(glam::Vec2::ONE)),
+            ),
+            color: tint.unwrap_or(Color::WHITE),
+            uv: texture.uv,
+            texture: 0,
+        }
+    }
+
     pub fn into_raw(self) -> RawInstance {
         RawInstance {
-            matrix: (self.camera * self.model).to_cols_array_2d(),
+            transform: self.model.matrix2.to_cols_array(),
+            translation: self.model.translation.to_array(),
             color: self.color.as_array(),
+            uv: self.uv.to_array(),
+            texture: self.texture,
         }
     }
 }
@@ -71,6 +114,7 @@ impl DrawInstance {
 pub enum DrawAttachment {
     Pipeline(Arc<wgpu::RenderPipeline>),
     Texture(Arc<wgpu::BindGroup>),
+    Camera(Arc<wgpu::BindGroup>),
 }
 
 impl DrawAttachment {
@@ -78,6 +122,7 @@ impl DrawAttachment {
         match self {
             Self::Pipeline(pipeline) => pass.set_pipeline(pipeline),
             Self::Texture(binding) => pass.set_bind_group(0, binding.as_ref(), &[]),
+            Self::Camera(binding) => pass.set_bind_group(1, binding.as_ref(), &[]),
         }
     }
 }
@@ -162,39 +207,62 @@ impl DrawBatch {
     }
 }
 
+/// Batches are split by camera slot as well as by texture binding (its address), since each
+/// camera has its own uniform.
+pub type BatchKey = (usize, usize);
+
+/// One draw call's worth of the frame, in the order it was requested.
+pub enum DrawStep {
+    /// Instances `range` of the batch keyed by `BatchKey`, added one after another.
+    Batch(BatchKey, Range<u32>),
+    /// A `SpriteBatch`, drawn with the camera in the given slot.
+    Retained(usize, Arc<Mutex<SpriteBatchData>>),
+}
+
 pub struct Batcher {
-    pub batches: HashMap<String, DrawBatch>,
+    pub batches: HashMap<BatchKey, DrawBatch>,
+    /// Sprites are grouped into batches to share buffers, but drawn in this order, so later
+    /// draws always land on top of earlier ones.
+    pub steps: Vec<DrawStep>,
 }
 
 impl Batcher {
     pub fn new() -> Self {
         Self {
             batches: HashMap::new(),
+            steps: Vec::new(),
         }
     }
 
     pub fn add(
         &mut self,
         device: &wgpu::Device,
-        id: &str,
+        id: BatchKey,
+        label: &str,
         attachments: &[DrawAttachment],
         instance: DrawInstance,
     ) {
-        match self.batches.get_mut(id) {
-            Some(batch) => batch.add(instance),
+        let index = match self.batches.get_mut(&id) {
+            Some(batch) => {
+                batch.add(instance);
+                batch.count - 1
+            }
             None => {
-                let batch = DrawBatch::new(device, id, attachments.to_vec(), vec![instance]);
+                let batch = DrawBatch::new(device, label, attachments.to_vec(), vec![instance]);
 
-                self.batches.insert(id.to_string(), batch);
+                self.batches.insert(id, batch);
+                0
             }
-        }
+        };
+
+        extend_steps(&mut self.steps, id, index);
     }
 
     pub fn cleanup(&mut self) {
-        let mut dead_batches: Vec<String> = Vec::new();
+        let mut dead_batches: Vec<BatchKey> = Vec::new();
         for id in self.batches.keys() {
             if self.batches[id].lifetime == 0 {
-                dead_batches.push(id.clone());
+                dead_batches.push(*id);
             }
         }
 
@@ -204,63 +272,158 @@ impl Batcher {
     }
 }
 
+/// Adds instance `index` of batch `id` to the last step if it directly follows it, or starts a
+/// new step otherwise.
+fn extend_steps(steps: &mut Vec<DrawStep>, id: BatchKey, index: u32) {
+    match steps.last_mut() {
+        Some(DrawStep::Batch(last, range)) if *last == id && range.end == index => {
+            range.end = index + 1;
+        }
+        _ => steps.push(DrawStep::Batch(id, index..index + 1)),
+    }
+}
+
 #[pyclass]
 pub struct Draw {
     pub device: Arc<wgpu::Device>,
     pub builtins: Arc<VideoBuiltins>,
     pub quad: Quad,
     pub viewport_size: glam::Vec2,
-    pub default_camera: glam::Mat4,
-    pub current_camera: Option<glam::Mat4>,
+    pub cameras: Vec<CameraBinding>,
+    pub camera_matrices: Vec<glam::Mat4>,
+    pub camera_bounds: Vec<(glam::Vec2, glam::Vec2)>,
+    pub current_camera: usize,
     pub batcher: Batcher,
+    pub culled: u32,
+    /// Present when the adapter supports binding arrays; otherwise sprites batch per texture.
+    pub arrays: Option<TextureArrays>,
 }
 
 impl Draw {
     pub fn new<'a>(video: &VideoStack, builtins: Arc<VideoBuiltins>) -> Self {
         let quad = Quad::new(video);
 
-        Self {
+        let mut draw = Self {
             device: Arc::clone(&video.device),
             builtins,
             quad,
             viewport_size: glam::Vec2::new(video.config.width as f32, video.config.height as f32),
-            default_camera: glam::Mat4::IDENTITY,
-            current_camera: None,
+            cameras: Vec::new(),
+            camera_matrices: Vec::new(),
+            camera_bounds: Vec::new(),
+            current_camera: 0,
             batcher: Batcher::new(),
-        }
+            culled: 0,
+            arrays: video.texture_arrays.then(TextureArrays::new),
+        };
+
+        draw.current_camera = draw.camera_slot(draw.default_camera());
+        draw
+    }
+
+    pub fn default_camera(&self) -> glam::Mat4 {
+        Camera::build_matrix(self.viewport_size, glam::Vec2::ZERO, 0.0, 1.0)
     }
 
     pub fn begin_frame(&mut self, video: &VideoStack) {
         self.viewport_size = glam::Vec2::new(video.config.width as f32, video.config.height as f32);
-        self.default_camera = Camera::build_matrix(self.viewport_size, glam::Vec2::ZERO, 0.0, 1.0);
+
+        self.camera_matrices.clear();
+        self.camera_bounds.clear();
+        self.culled = 0;
+        self.current_camera = self.camera_slot(self.default_camera());
+    }
+
+    /// Returns the camera slot holding `matrix` this frame, allocating one if needed.
+    /// Slots (and their uniform buffers) are kept across frames so batches can be reused.
+    pub fn camera_slot(&mut self, matrix: glam::Mat4) -> usize {
+        if let Some(slot) = self.camera_matrices.iter().position(|x| *x == matrix) {
+            return slot;
+        }
+
+        if self.camera_matrices.len() == self.cameras.len() {
+            self.cameras.push(CameraBinding::new(
+                &self.device,
+                &self.builtins.layouts.camera,
+            ));
+        }
+
+        self.camera_matrices.push(matrix);
+        self.camera_bounds.push(Camera::visible_bounds(matrix));
+        self.camera_matrices.len() - 1
+    }
+
+    /// Whether `instance` lies entirely outside of the current camera's view.
+    pub fn is_culled(&self, instance: &DrawInstance) -> bool {
+        !bounds_overlap(
+            self.camera_bounds[self.current_camera],
+            model_bounds(&instance.model),
+        )
     }
 
     pub fn submit(&mut self, video: &VideoStack, pass: &mut wgpu::RenderPass) {
+        self.builtins.mipmaps.flush();
+
+        for (camera, matrix) in self.cameras.iter().zip(&self.camera_matrices) {
+            camera.write(&video.queue, *matrix);
+        }
+
         self.quad.apply(pass);
 
         for batch in self.batcher.batches.values_mut() {
-            let count = batch.write(video);
-
-            if count == 0 {
-                continue;
-            }
+            batch.write(video);
+        }
 
-            for attachment in &batch.attachments {
-                attachment.attach(pass);
+        for step in self.batcher.steps.drain(..) {
+            match step {
+                DrawStep::Batch(id, range) => {
+                    let batch = &self.batcher.batches[&id];
+
+                    for attachment in &batch.attachments {
+                        attachment.attach(pass);
+                    }
+
+                    pass.set_vertex_buffer(1, batch.buffer.slice(..));
+                    pass.draw_indexed(0..Quad::NUM_INDICES, 0, range);
+                }
+                DrawStep::Retained(camera, batch) => {
+                    let mut batch = batch.lock().unwrap();
+                    batch.upload(video);
+
+                    let buffer = match batch.buffer.as_ref() {
+                        Some(buffer) => buffer,
+                        None => continue,
+                    };
+
+                    pass.set_pipeline(&self.builtins.pipelines.sprite.pipeline);
+                    pass.set_bind_group(1, self.cameras[camera].binding.as_ref(), &[]);
+                    pass.set_vertex_buffer(1, buffer.slice(..));
+
+                    for (binding, range) in &batch.runs {
+                        pass.set_bind_group(0, binding.as_ref(), &[]);
+                        pass.draw_indexed(0..Quad::NUM_INDICES, 0, range.clone());
+                    }
+                }
             }
-
-            pass.set_vertex_buffer(1, batch.buffer.slice(..));
-            pass.draw_indexed(0..Quad::NUM_INDICES, 0, 0..count);
         }
 
         self.batcher.cleanup();
+
+        if let Some(arrays) = self.arrays.as_mut() {
+            arrays.collect(&self.device, &self.builtins);
+        }
     }
 }
 
 #[pymethods]
 impl Draw {
     pub fn set_camera<'a>(&mut self, py: Python<'a>, camera: Option<&Camera>) {
-        self.current_camera = camera.map(|x| x.matrix(py, self.viewport_size));
+        let matrix = match camera {
+            Some(camera) => camera.matrix(py, self.viewport_size),
+            None => self.default_camera(),
+        };
+
+        self.current_camera = self.camera_slot(matrix);
     }
 
     pub fn sprite(
@@ -271,22 +434,158 @@ impl Draw {
         scale: Option<Vec2>,
         tint: Option<Color>,
     ) {
-        self.batcher.add(
-            &self.device,
-            &texture.path,
-            &[
-                DrawAttachment::Pipeline(Arc::clone(&self.builtins.pipelines.sprite.pipeline)),
-                DrawAttachment::Texture(Arc::clone(&texture.binding)),
-            ],
-            DrawInstance {
-                camera: self.current_camera.unwrap_or(self.default_camera),
-                model: model_matrix(
-                    &position,
-                    rotation.unwrap_or(0.0),
-                    &(*texture.size * scale.map(|x| x.into()).unwrap_or(glam::Vec2::ONE)),
+        let mut instance = DrawInstance::sprite(texture, position, rotation, scale, tint);
+        if self.is_culled(&instance) {
+            self.culled += 1;
+            return;
+        }
+
+        let camera = DrawAttachment::Camera(Arc::clone(&self.cameras[self.current_camera].binding));
+
+        match (self.arrays.as_mut(), &self.builtins.pipelines.sprite_array) {
+            (Some(arrays), Some(pipeline)) => {
+                let (set, slot) = arrays.slot(&self.device, &self.builtins, texture);
+                let set = &arrays.sets[set];
+                let binding = set.binding.as_ref().expect("Texture set has no binding");
+                instance.texture = slot;
+
+                self.batcher.add(
+                    &self.device,
+                    (self.current_camera, Arc::as_ptr(binding) as usize),
+                    &set.label,
+                    &[
+                        DrawAttachment::Pipeline(Arc::clone(&pipeline.pipeline)),
+                        DrawAttachment::Texture(Arc::clone(binding)),
+                        camera,
+                    ],
+                    instance,
+                );
+            }
+            _ => {
+                self.batcher.add(
+                    &self.device,
+                    (self.current_camera, Arc::as_ptr(&texture.binding) as usize),
+                    &texture.path,
+                    &[
+                        DrawAttachment::Pipeline(Arc::clone(
+                            &self.builtins.pipelines.sprite.pipeline,
+                        )),
+                        DrawAttachment::Texture(Arc::clone(&texture.binding)),
+                        camera,
+                    ],
+                    instance,
+                );
+            }
+        }
+    }
+
+    pub fn batch(&mut self, batch: &SpriteBatch) {
+        self.batcher.steps.push(DrawStep::Retained(
+            self.current_camera,
+            Arc::clone(&batch.data),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{hint::black_box, time::Instant};
+
+    use super::{extend_steps, DrawInstance, DrawStep, RawInstance};
+    use crate::{graphics::camera::Camera, math::colors::Color};
+
+    const INSTANCES: usize = 1_000_000;
+
+    /// The instance format used before the camera moved into a uniform buffer.
+    #[repr(C)]
+    #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    struct LegacyRawInstance {
+        matrix: [[f32; 4]; 4],
+        color: [f32; 4],
+    }
+
+    fn legacy_into_raw(
+        camera: glam::Mat4,
+        position: glam::Vec2,
+        rotation: f32,
+    ) -> LegacyRawInstance {
+        let model = glam::Mat4::from_translation(position.extend(0.0))
+            * glam::Mat4::from_rotation_z(rotation)
+            * glam::Mat4::from_scale(glam::Vec3::new(32.0, 32.0, 1.0));
+
+        LegacyRawInstance {
+            matrix: (camera * model).to_cols_array_2d(),
+            color: Color::WHITE.as_array(),
+        }
+    }
+
+    fn instances_per_ms(f: impl Fn(usize) -> usize) -> f64 {
+        let start = Instant::now();
+        let mut bytes = 0;
+        for i in 0..INSTANCES {
+            bytes += f(i);
+        }
+        black_box(bytes);
+
+        INSTANCES as f64 / (start.elapsed().as_secs_f64() * 1000.0)
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn instance_packing_benchmark() {
+        let camera =
+            Camera::build_matrix(glam::Vec2::new(1280.0, 720.0), glam::Vec2::ZERO, 0.0, 1.0);
+
+        let before = instances_per_ms(|i| {
+            let raw = black_box(legacy_into_raw(camera, glam::Vec2::splat(i as f32), 0.5));
+            bytemuck::bytes_of(&raw).len()
+        });
+
+        let after = instances_per_ms(|i| {
+            let instance = DrawInstance {
+                model: crate::math::matrices::model_matrix(
+                    &glam::Vec2::splat(i as f32),
+                    0.5,
+                    &glam::Vec2::splat(32.0),
                 ),
-                color: tint.unwrap_or(Color::WHITE),
-            },
+                color: Color::WHITE,
+                uv: glam::Vec4::new(0.0, 0.0, 1.0, 1.0),
+                texture: 0,
+            };
+            let raw = black_box(instance.into_raw());
+            bytemuck::bytes_of(&raw).len()
+        });
+
+        println!(
+            "before: {before:.0} instances/ms ({} bytes each)",
+            std::mem::size_of::<LegacyRawInstance>()
+        );
+        println!(
+            "after: {after:.0} instances/ms ({} bytes each)",
+            std::mem::size_of::<RawInstance>()
         );
     }
+
+    #[test]
+    fn steps_keep_draw_order_across_batches() {
+        let (a, b) = ((0, 1), (0, 2));
+        let mut steps = Vec::new();
+
+        // Sprites of `a`, then `b`, then `a` again must not be merged into one draw of `a`.
+        extend_steps(&mut steps, a, 0);
+        extend_steps(&mut steps, a, 1);
+        extend_steps(&mut steps, b, 0);
+        extend_steps(&mut steps, a, 2);
+
+        let ranges: Vec<_> = steps
+            .iter()
+            .map(|step| match step {
+                DrawStep::Batch(id, range) => (*id, range.clone()),
+                DrawStep::Retained(..) => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(ranges, vec![(a, 0..2), (b, 0..1), (a, 2..3)]);
+    }
 }
@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex};
+
+/// Fills in the mip chain of textures by repeatedly downsampling each level into the next.
+#[derive(Debug)]
+pub struct MipmapGenerator {
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+    pub layout: Arc<wgpu::BindGroupLayout>,
+    pub pipeline: wgpu::RenderPipeline,
+    pub sampler: wgpu::Sampler,
+    /// Textures written since their mip chain was last generated.
+    pub dirty: Mutex<Vec<Arc<wgpu::Texture>>>,
+}
+
+impl MipmapGenerator {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+    pub fn new(
+        device: &Arc<wgpu::Device>,
+        queue: &Arc<wgpu::Queue>,
+        layout: &Arc<wgpu::BindGroupLayout>,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap shader"),
+            source: wgpu::ShaderSource::Wgsl(mink_shaders::MIPMAP.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mipmap pipeline layout"),
+            bind_group_layouts: &[layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::all(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            device: Arc::clone(device),
+            queue: Arc::clone(queue),
+            layout: Arc::clone(layout),
+            pipeline,
+            sampler,
+            dirty: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Regenerates the mip chain of `texture` at the next [`Self::flush`], so any number of
+    /// writes before then cost a single pass.
+    pub fn mark_dirty(&self, texture: &Arc<wgpu::Texture>) {
+        let mut dirty = self.dirty.lock().unwrap();
+
+        if !dirty.iter().any(|x| Arc::ptr_eq(x, texture)) {
+            dirty.push(Arc::clone(texture));
+        }
+    }
+
+    /// Regenerates the mip chains of every texture written since the last flush. Called before
+    /// drawing, as only sampling reads the levels after the first.
+    pub fn flush(&self) {
+        let dirty = std::mem::take(&mut *self.dirty.lock().unwrap());
+
+        for texture in dirty {
+            self.generate(&texture);
+        }
+    }
+
+    /// The number of levels in a full mip chain for a texture of the given size.
+    pub fn level_count(width: u32, height: u32) -> u32 {
+        u32::BITS - width.max(height).max(1).leading_zeros()
+    }
+
+    /// Regenerates every level of `texture` after the first from the first. The texture must
+    /// be in [`Self::FORMAT`] and usable as a render attachment.
+    pub fn generate(&self, texture: &wgpu::Texture) {
+        let views: Vec<wgpu::TextureView> = (0..texture.mip_level_count())
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mip level view"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Mipmap encoder"),
+            });
+
+        for pair in views.windows(2) {
+            let binding = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mip level binding"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&pair[0]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &pair[1],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &binding, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        self.queue.submit([encoder.finish()]);
+    }
+}
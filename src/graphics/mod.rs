@@ -1,7 +1,10 @@
 pub mod api;
+pub mod arrays;
+pub mod batch;
 pub mod builtin;
 pub mod camera;
 pub mod draw;
+pub mod mipmaps;
 pub mod pipeline;
 pub mod quad;
 pub mod stack;
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use pollster::FutureExt;
 use winit::window::Window;
 
-use crate::graphics::{draw::Draw, target::RenderTarget};
+use crate::graphics::{arrays::TextureArrays, draw::Draw, target::RenderTarget};
 
 #[allow(dead_code)]
 pub struct VideoStack<'a> {
@@ -14,6 +14,8 @@ pub struct VideoStack<'a> {
     pub queue: Arc<wgpu::Queue>,
     pub config: wgpu::SurfaceConfiguration,
     pub target: RenderTarget,
+    /// Whether sprites can be batched across textures through binding arrays.
+    pub texture_arrays: bool,
 }
 
 impl<'a> VideoStack<'a> {
@@ -46,11 +48,34 @@ impl<'a> VideoStack<'a> {
 
         println!("{:#?}", adapter.get_info());
 
+        let array_features = wgpu::Features::TEXTURE_BINDING_ARRAY
+            | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING;
+        let texture_arrays = adapter.features().contains(array_features)
+            && adapter.limits().max_binding_array_elements_per_shader_stage >= TextureArrays::SIZE;
+
+        let (mut required_features, required_limits) = if texture_arrays {
+            (
+                array_features,
+                wgpu::Limits {
+                    max_binding_array_elements_per_shader_stage: TextureArrays::SIZE,
+                    ..Default::default()
+                },
+            )
+        } else {
+            (wgpu::Features::default(), wgpu::Limits::default())
+        };
+
+        // Compressed textures are decoded on the CPU when their format isn't supported.
+        required_features |= adapter.features()
+            & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: Some("Mink graphics device."),
-                required_features: wgpu::Features::default(),
-                required_limits: wgpu::Limits::default(),
+                required_features,
+                required_limits,
                 memory_hints: wgpu::MemoryHints::default(),
                 trace: wgpu::Trace::Off,
             })
@@ -80,6 +105,7 @@ impl<'a> VideoStack<'a> {
             queue: Arc::new(queue),
             config,
             target,
+            texture_arrays,
         }
     }
 
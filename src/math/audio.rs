@@ -1,4 +1,7 @@
-use pyo3::prelude::*;
+use std::time::Duration;
+
+use kira::{Easing, StartTime, Tween};
+use pyo3::{exceptions::PyValueError, prelude::*};
 
 #[pyfunction]
 pub fn linear_to_db(value: f32) -> f32 {
@@ -19,3 +22,26 @@ pub fn db_to_linear(value: f32) -> f32 {
         10.0f32.powf(value / 20.0)
     }
 }
+
+/// A tween starting now and lasting `seconds`, or an instant change if that is zero, negative
+/// or not a finite number.
+pub fn tween(seconds: f64, easing: Easing) -> Tween {
+    Tween {
+        start_time: StartTime::Immediate,
+        duration: Duration::try_from_secs_f64(seconds).unwrap_or_default(),
+        easing,
+    }
+}
+
+/// Parses the name of an easing curve: "linear", "in", "out" or "in_out".
+pub fn easing(name: &str) -> PyResult<Easing> {
+    match name {
+        "linear" => Ok(Easing::Linear),
+        "in" => Ok(Easing::InPowi(2)),
+        "out" => Ok(Easing::OutPowi(2)),
+        "in_out" => Ok(Easing::InOutPowi(2)),
+        name => Err(PyValueError::new_err(format!(
+            "Unknown easing '{name}', expected 'linear', 'in', 'out' or 'in_out'"
+        ))),
+    }
+}
@@ -26,6 +26,11 @@ impl Color {
         Self::from_hsva(h, s, v, 1.0)
     }
 
+    /// The color as 8-bit channels, clamped to the displayable range.
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a].map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
     pub fn from_hsva(h: f32, s: f32, v: f32, a: f32) -> Self {
         let h = h.rem_euclid(360.0);
 
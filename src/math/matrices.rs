@@ -1,7 +1,18 @@
-use glam::{Mat4, Vec2, Vec3};
+use glam::{Affine2, Vec2};
 
-pub fn model_matrix(position: &Vec2, rotation: f32, size: &Vec2) -> Mat4 {
-    Mat4::from_translation(Vec3::new(position.x, position.y, 0.0))
-        * Mat4::from_rotation_z(rotation)
-        * Mat4::from_scale(Vec3::new(size.x, size.y, 1.0))
+pub fn model_matrix(position: &Vec2, rotation: f32, size: &Vec2) -> Affine2 {
+    Affine2::from_scale_angle_translation(*size, rotation, *position)
+}
+
+/// The bounding box (min, max) of the unit quad transformed by `model`.
+pub fn model_bounds(model: &Affine2) -> (Vec2, Vec2) {
+    let extents = (model.matrix2.x_axis.abs() + model.matrix2.y_axis.abs()) * 0.5;
+    let center = model.translation;
+
+    (center - extents, center + extents)
+}
+
+/// Whether the bounding boxes (min, max) `a` and `b` share any point, edges included.
+pub fn bounds_overlap(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> bool {
+    a.0.x <= b.1.x && a.0.y <= b.1.y && b.0.x <= a.1.x && b.0.y <= a.1.y
 }
@@ -161,8 +161,16 @@ impl<'a> ApplicationHandler for Runtime<'a> {
                 }
                 self.last_frame = Instant::now();
 
+                if let Some(mut assets) = self.global_assets.as_ref().map(|x| x.borrow_mut()) {
+                    assets.poll(self.py);
+                }
+
                 self.update_fn.call0().unwrap();
 
+                if let Some(mut audio) = self.global_audio.as_ref().map(|x| x.borrow_mut()) {
+                    audio.update(self.py);
+                }
+
                 let video = match self.video.as_mut() {
                     Some(video) => video,
                     None => return,
@@ -179,6 +187,10 @@ impl<'a> ApplicationHandler for Runtime<'a> {
                     None => return,
                 };
 
+                if let Some(mut stats) = self.global_stats.as_ref().map(|x| x.borrow_mut()) {
+                    stats.culled = draw.culled;
+                }
+
                 if let Err(e) = video.submit(&mut draw) {
                     match e {
                         wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
@@ -3,10 +3,19 @@ use pyo3::prelude::*;
 pub mod api;
 
 #[pyclass]
-pub struct Stats {}
+pub struct Stats {
+    pub culled: u32,
+}
 
 impl Stats {
     pub fn new() -> Self {
-        Self {}
+        Self { culled: 0 }
+    }
+}
+
+#[pymethods]
+impl Stats {
+    pub fn culled(&self) -> u32 {
+        self.culled
     }
 }